@@ -0,0 +1,30 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const AUDIT_FILE: &str = "audit.log";
+
+/// Appends a line to `{data_dir}/audit.log` (and stdout) for sensitive operations.
+pub fn log(data_dir: &str, event: &str, peer: Option<String>, outcome: &str) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default();
+    let line = format!(
+        "{} {} peer={} {}",
+        timestamp,
+        event,
+        peer.unwrap_or_else(|| "unknown".to_string()),
+        outcome
+    );
+    println!("audit: {}", line);
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(Path::new(data_dir).join(AUDIT_FILE))
+        .and_then(|mut file| writeln!(file, "{}", line));
+    if let Err(e) = result {
+        println!("failed to write the audit log: {}", e);
+    }
+}
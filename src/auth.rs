@@ -0,0 +1,352 @@
+use crate::error::ShiroError;
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header, Method};
+use actix_web::{Error, HttpMessage, HttpResponse, ResponseError};
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// What a token allows, each scope includes the ones before it.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Scope {
+    /// Balances, assets, transfers and unspents
+    Read,
+    /// Addresses, blinded UTXOs and refresh
+    Receive,
+    /// Issuance, sends, UTXO creation and PSBT signing
+    Send,
+    /// Wallet creation and removal, secrets and draining
+    Admin,
+}
+
+impl FromStr for Scope {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(Self::Read),
+            "receive" => Ok(Self::Receive),
+            "send" => Ok(Self::Send),
+            "admin" => Ok(Self::Admin),
+            _ => Err(format!("Unknown scope: {s}")),
+        }
+    }
+}
+
+/// `{scope}:{token}` given with `--api-token`
+pub struct ApiToken {
+    scope: Scope,
+    token: String,
+}
+
+impl FromStr for ApiToken {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((scope, token)) if !token.is_empty() => Ok(ApiToken {
+                scope: scope.parse::<Scope>()?,
+                token: token.to_string(),
+            }),
+            _ => Err("an API token should be given as {scope}:{token}".to_string()),
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Scope needed to call an endpoint, `None` for public ones.
+/// `path` should be percent-decoded the way the router sees it.
+pub fn required_scope(method: &Method, path: &str) -> Option<Scope> {
+    if path == "/wallets" {
+        return Some(Scope::Admin);
+    }
+    let rest = if let Some(rest) = path.strip_prefix("/wallets/") {
+        match rest.find('/') {
+            Some(pos) => &rest[pos..],
+            None => "",
+        }
+    } else if path == "/wallet" || path.starts_with("/wallet/") {
+        &path["/wallet".len()..]
+    } else if path.starts_with("/wallet") {
+        return Some(Scope::Admin);
+    } else if path == "/healthz" || path == "/keys" {
+        return None;
+    } else if *method == Method::GET || *method == Method::HEAD {
+        // The frontend files
+        return None;
+    } else {
+        return Some(Scope::Admin);
+    };
+    let scope = match rest {
+        "/asset_balance" | "/assets" | "/btc_balance" | "/dir" | "/events" | "/fees"
+        | "/invoice" | "/refresh/status" | "/transactions" | "/unspents" => Scope::Read,
+        "/transfers" if *method != Method::DELETE => Scope::Read,
+        "/address" | "/blind" | "/refresh" => Scope::Receive,
+        "/transfers" | "/transfers/fail" | "/psbt/sign" => Scope::Send,
+        _ if rest.starts_with("/assets/") => Scope::Read,
+        _ if rest.starts_with("/send") || rest.starts_with("/utxos") => Scope::Send,
+        _ if rest.starts_with("/btc/") => Scope::Send,
+        _ if rest.starts_with("/issue/") => Scope::Send,
+        _ => Scope::Admin,
+    };
+    Some(scope)
+}
+
+/// Checks the `Authorization: Bearer {token}` header against the configured tokens.
+/// Every request is let through when no token is configured, see `--insecure-no-auth`.
+#[derive(Clone)]
+pub struct Authentication {
+    tokens: Arc<Vec<ApiToken>>,
+}
+
+impl Authentication {
+    pub fn new(tokens: Vec<ApiToken>) -> Authentication {
+        Authentication {
+            tokens: Arc::new(tokens),
+        }
+    }
+
+    /// Whether `token` is a configured admin token, always false when none is configured.
+    pub fn is_admin_token(&self, token: &str) -> bool {
+        self.tokens.iter().any(|x| {
+            x.scope == Scope::Admin && constant_time_eq(x.token.as_bytes(), token.as_bytes())
+        })
+    }
+
+    fn check(&self, req: &ServiceRequest) -> Result<(), HttpResponse> {
+        if self.tokens.is_empty() {
+            return Ok(());
+        }
+        // The router matches the percent-decoded path, `req.path()` is the raw one.
+        let required = match required_scope(req.method(), req.match_info().unprocessed()) {
+            Some(required) => required,
+            None => return Ok(()),
+        };
+        let token = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let scope = match token.and_then(|token| {
+            self.tokens
+                .iter()
+                .find(|x| constant_time_eq(x.token.as_bytes(), token.as_bytes()))
+        }) {
+            Some(api_token) => api_token.scope,
+            None => return Err(ShiroError::Unauthorized.error_response()),
+        };
+        if scope < required {
+            return Err(ShiroError::Forbidden(
+                "API token doesn't allow this operation".to_string(),
+            )
+            .error_response());
+        }
+        req.extensions_mut().insert(scope);
+        Ok(())
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Authentication
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = AuthenticationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthenticationMiddleware {
+            service,
+            authentication: self.clone(),
+        }))
+    }
+}
+
+pub struct AuthenticationMiddleware<S> {
+    service: S,
+    authentication: Authentication,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthenticationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        match self.authentication.check(&req) {
+            Ok(_) => {
+                let fut = self.service.call(req);
+                Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+            }
+            Err(resp) => {
+                let resp = req.into_response(resp);
+                Box::pin(async move { Ok(resp.map_into_right_body()) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::ShiroWallet;
+    use actix_web::{http, test, web, App};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_api_token() {
+        let api_token = "send:secret".parse::<ApiToken>().unwrap();
+        assert_eq!(api_token.scope, Scope::Send);
+        assert_eq!(api_token.token, "secret");
+        assert!("secret".parse::<ApiToken>().is_err());
+        assert!("root:secret".parse::<ApiToken>().is_err());
+        assert!("admin:".parse::<ApiToken>().is_err());
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(required_scope(&Method::GET, "/healthz"), None);
+        assert_eq!(required_scope(&Method::GET, "/index.html"), None);
+        assert_eq!(required_scope(&Method::PUT, "/keys"), None);
+        assert_eq!(
+            required_scope(&Method::POST, "/unknown"),
+            Some(Scope::Admin)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/wallet_data"),
+            Some(Scope::Admin)
+        );
+        assert_eq!(
+            required_scope(&Method::PUT, "/wallet/unspents"),
+            Some(Scope::Read)
+        );
+        assert_eq!(
+            required_scope(&Method::PUT, "/wallets/alice/transfers"),
+            Some(Scope::Read)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/wallets/alice/assets/rgb1"),
+            Some(Scope::Read)
+        );
+        assert_eq!(
+            required_scope(&Method::DELETE, "/wallet/transfers"),
+            Some(Scope::Send)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/wallets/alice/events"),
+            Some(Scope::Read)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/wallet/refresh/status"),
+            Some(Scope::Read)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/wallet/transfers/fail"),
+            Some(Scope::Send)
+        );
+        assert_eq!(
+            required_scope(&Method::PUT, "/wallet/blind"),
+            Some(Scope::Receive)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/wallet/send/begin"),
+            Some(Scope::Send)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/wallet/btc/send"),
+            Some(Scope::Send)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/wallet/btc/send/end"),
+            Some(Scope::Send)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/wallet/data"),
+            Some(Scope::Admin)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/wallets/alice/backup"),
+            Some(Scope::Admin)
+        );
+        assert_eq!(required_scope(&Method::PUT, "/wallet"), Some(Scope::Admin));
+        assert_eq!(
+            required_scope(&Method::DELETE, "/wallets/alice"),
+            Some(Scope::Admin)
+        );
+        assert_eq!(required_scope(&Method::GET, "/wallets"), Some(Scope::Admin));
+    }
+
+    #[actix_web::test]
+    async fn test_authentication() {
+        let tokens = vec![
+            "read:reader".parse::<ApiToken>().unwrap(),
+            "admin:administrator".parse::<ApiToken>().unwrap(),
+        ];
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Mutex::new(ShiroWallet::new())))
+                .wrap(Authentication::new(tokens))
+                .service(crate::healthz::get)
+                .service(crate::wallet::dir::get)
+                .service(crate::wallet::data::get),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/healthz").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let req = test::TestRequest::get().uri("/wallet/dir").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::get()
+            .uri("/wallet/dir")
+            .insert_header((header::AUTHORIZATION, "Bearer unknown"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::get()
+            .uri("/wallet/data")
+            .insert_header((header::AUTHORIZATION, "Bearer reader"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+        // The router decodes `%77allet` to `wallet`, so does the authentication.
+        let req = test::TestRequest::get().uri("/%77allet/data").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::get()
+            .uri("/%77allet/data")
+            .insert_header((header::AUTHORIZATION, "Bearer reader"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+        // Authenticated, the handler answers that there is no wallet yet.
+        let req = test::TestRequest::get()
+            .uri("/wallet/data")
+            .insert_header((header::AUTHORIZATION, "Bearer administrator"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::PRECONDITION_FAILED);
+    }
+}
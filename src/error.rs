@@ -0,0 +1,266 @@
+use actix_multipart::form::MultipartFormConfig;
+use actix_web::http::{header, StatusCode};
+use actix_web::{web, HttpResponse, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+
+/// Body of every error response
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ErrorResponse {
+    /// Stable identifier of the error, like `WALLET_NOT_ONLINE` or `ASSET_NOT_FOUND`
+    pub code: String,
+    pub message: String,
+    pub details: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ShiroError {
+    WalletNotFound,
+    MediaNotFound,
+    WalletNotCreated,
+    WalletAlreadyCreated,
+    WalletNotOnline,
+    WatchOnly,
+    PassphraseNotSet,
+    WrongPassphrase,
+    Unauthorized,
+    Forbidden(String),
+    TooManyRequests,
+    /// The Electrum server couldn't be reached outside of rgb-lib
+    Electrum(String),
+    InvalidParams(String),
+    /// Like `InvalidParams`, with the name of the field in `details`
+    InvalidField(String, String),
+    Internal(String),
+    Rgb(rgb_lib::Error),
+}
+
+/// Code and status of an rgb-lib error. The match is exhaustive on purpose: a new
+/// variant in rgb-lib has to be given a code here before the crate builds again.
+fn rgb_error_code(e: &rgb_lib::Error) -> (&'static str, StatusCode) {
+    use rgb_lib::Error as E;
+    match e {
+        E::AllocationsAlreadyAvailable { .. } => {
+            ("ALLOCATIONS_ALREADY_AVAILABLE", StatusCode::CONFLICT)
+        }
+        E::AssetNotFound { .. } => ("ASSET_NOT_FOUND", StatusCode::NOT_FOUND),
+        E::BatchTransferNotFound { .. } => ("BATCH_TRANSFER_NOT_FOUND", StatusCode::NOT_FOUND),
+        E::CannotChangeOnline { .. } => ("CANNOT_CHANGE_ONLINE", StatusCode::CONFLICT),
+        E::CannotDeleteTransfer { .. } => ("CANNOT_DELETE_TRANSFER", StatusCode::BAD_REQUEST),
+        E::CannotFailTransfer { .. } => ("CANNOT_FAIL_TRANSFER", StatusCode::BAD_REQUEST),
+        E::FailedBdkSync { .. } => ("FAILED_BDK_SYNC", StatusCode::SERVICE_UNAVAILABLE),
+        E::FailedBroadcast { .. } => ("FAILED_BROADCAST", StatusCode::SERVICE_UNAVAILABLE),
+        E::FailedIssuance { .. } => ("FAILED_ISSUANCE", StatusCode::INTERNAL_SERVER_ERROR),
+        E::FileAlreadyExists { .. } => ("FILE_ALREADY_EXISTS", StatusCode::CONFLICT),
+        E::IO { .. } => ("IO", StatusCode::INTERNAL_SERVER_ERROR),
+        E::Inconsistency { .. } => ("INCONSISTENCY", StatusCode::INTERNAL_SERVER_ERROR),
+        E::InexistentDataDir { .. } => ("INEXISTENT_DATA_DIR", StatusCode::INTERNAL_SERVER_ERROR),
+        E::InsufficientAllocationSlots { .. } => (
+            "INSUFFICIENT_ALLOCATION_SLOTS",
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        E::InsufficientBitcoins { .. } => {
+            ("INSUFFICIENT_BITCOINS", StatusCode::UNPROCESSABLE_ENTITY)
+        }
+        E::InsufficientSpendableAssets { .. } => (
+            "INSUFFICIENT_SPENDABLE_ASSETS",
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        E::InsufficientTotalAssets { .. } => (
+            "INSUFFICIENT_TOTAL_ASSETS",
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        E::Internal { .. } => ("INTERNAL", StatusCode::INTERNAL_SERVER_ERROR),
+        E::InvalidAddress { .. } => ("INVALID_ADDRESS", StatusCode::BAD_REQUEST),
+        E::InvalidAmountZero { .. } => ("INVALID_AMOUNT_ZERO", StatusCode::BAD_REQUEST),
+        E::InvalidAssetID { .. } => ("INVALID_ASSET_ID", StatusCode::BAD_REQUEST),
+        E::InvalidBitcoinKeys { .. } => ("INVALID_BITCOIN_KEYS", StatusCode::BAD_REQUEST),
+        E::InvalidBitcoinNetwork { .. } => ("INVALID_BITCOIN_NETWORK", StatusCode::BAD_REQUEST),
+        E::InvalidBlindedUTXO { .. } => ("INVALID_BLINDED_UTXO", StatusCode::BAD_REQUEST),
+        E::InvalidConsignment { .. } => ("INVALID_CONSIGNMENT", StatusCode::BAD_REQUEST),
+        E::InvalidDescription { .. } => ("INVALID_DESCRIPTION", StatusCode::BAD_REQUEST),
+        E::InvalidElectrum { .. } => ("INVALID_ELECTRUM", StatusCode::SERVICE_UNAVAILABLE),
+        E::InvalidFeeRate { .. } => ("INVALID_FEE_RATE", StatusCode::BAD_REQUEST),
+        E::InvalidFilePath { .. } => ("INVALID_FILE_PATH", StatusCode::BAD_REQUEST),
+        E::InvalidInvoice { .. } => ("INVALID_INVOICE", StatusCode::BAD_REQUEST),
+        E::InvalidMnemonic { .. } => ("INVALID_MNEMONIC", StatusCode::BAD_REQUEST),
+        E::InvalidName { .. } => ("INVALID_NAME", StatusCode::BAD_REQUEST),
+        E::InvalidOnline { .. } => ("INVALID_ONLINE", StatusCode::PRECONDITION_FAILED),
+        E::InvalidPrecision { .. } => ("INVALID_PRECISION", StatusCode::BAD_REQUEST),
+        E::InvalidProxyProtocol { .. } => {
+            ("INVALID_PROXY_PROTOCOL", StatusCode::SERVICE_UNAVAILABLE)
+        }
+        E::InvalidPsbt { .. } => ("INVALID_PSBT", StatusCode::BAD_REQUEST),
+        E::InvalidPubkey { .. } => ("INVALID_PUBKEY", StatusCode::BAD_REQUEST),
+        E::InvalidRecipientID { .. } => ("INVALID_RECIPIENT_ID", StatusCode::BAD_REQUEST),
+        E::InvalidTicker { .. } => ("INVALID_TICKER", StatusCode::BAD_REQUEST),
+        E::InvalidTransportEndpoint { .. } => {
+            ("INVALID_TRANSPORT_ENDPOINT", StatusCode::BAD_REQUEST)
+        }
+        E::InvalidTransportEndpoints { .. } => {
+            ("INVALID_TRANSPORT_ENDPOINTS", StatusCode::BAD_REQUEST)
+        }
+        E::InvalidTxid { .. } => ("INVALID_TXID", StatusCode::BAD_REQUEST),
+        E::InvalidVanillaKeychain { .. } => ("INVALID_VANILLA_KEYCHAIN", StatusCode::BAD_REQUEST),
+        E::NoIssuanceAmounts { .. } => ("NO_ISSUANCE_AMOUNTS", StatusCode::BAD_REQUEST),
+        E::NoValidTransportEndpoint { .. } => (
+            "NO_VALID_TRANSPORT_ENDPOINT",
+            StatusCode::SERVICE_UNAVAILABLE,
+        ),
+        E::Offline { .. } => ("OFFLINE", StatusCode::PRECONDITION_FAILED),
+        E::OutputBelowDustLimit { .. } => ("OUTPUT_BELOW_DUST_LIMIT", StatusCode::BAD_REQUEST),
+        E::Proxy { .. } => ("PROXY", StatusCode::SERVICE_UNAVAILABLE),
+        E::RecipientIDAlreadyUsed { .. } => ("RECIPIENT_ID_ALREADY_USED", StatusCode::CONFLICT),
+        E::TooHighIssuanceAmounts { .. } => ("TOO_HIGH_ISSUANCE_AMOUNTS", StatusCode::BAD_REQUEST),
+        E::TransferNotFound { .. } => ("TRANSFER_NOT_FOUND", StatusCode::NOT_FOUND),
+        E::UnknownRgbInterface { .. } => ("UNKNOWN_RGB_INTERFACE", StatusCode::BAD_REQUEST),
+        E::UnknownRgbSchema { .. } => ("UNKNOWN_RGB_SCHEMA", StatusCode::BAD_REQUEST),
+        E::UnsupportedBackupVersion { .. } => {
+            ("UNSUPPORTED_BACKUP_VERSION", StatusCode::BAD_REQUEST)
+        }
+        E::UnsupportedTransportType { .. } => {
+            ("UNSUPPORTED_TRANSPORT_TYPE", StatusCode::BAD_REQUEST)
+        }
+        E::WatchOnly { .. } => ("WATCH_ONLY", StatusCode::FORBIDDEN),
+        E::WrongPassword { .. } => ("WRONG_PASSWORD", StatusCode::FORBIDDEN),
+    }
+}
+
+impl ShiroError {
+    pub fn code(&self) -> String {
+        match self {
+            Self::WalletNotFound => "WALLET_NOT_FOUND".to_string(),
+            Self::MediaNotFound => "MEDIA_NOT_FOUND".to_string(),
+            Self::WalletNotCreated => "WALLET_NOT_CREATED".to_string(),
+            Self::WalletAlreadyCreated => "WALLET_ALREADY_CREATED".to_string(),
+            Self::WalletNotOnline => "WALLET_NOT_ONLINE".to_string(),
+            Self::WatchOnly => "WATCH_ONLY".to_string(),
+            Self::PassphraseNotSet => "PASSPHRASE_NOT_SET".to_string(),
+            Self::WrongPassphrase => "WRONG_PASSPHRASE".to_string(),
+            Self::Unauthorized => "UNAUTHORIZED".to_string(),
+            Self::Forbidden(_) => "FORBIDDEN".to_string(),
+            Self::TooManyRequests => "TOO_MANY_REQUESTS".to_string(),
+            Self::Electrum(_) => "ELECTRUM".to_string(),
+            Self::InvalidParams(_) | Self::InvalidField(_, _) => "INVALID_PARAMS".to_string(),
+            Self::Internal(_) => "INTERNAL".to_string(),
+            Self::Rgb(e) => rgb_error_code(e).0.to_string(),
+        }
+    }
+
+    fn details(&self) -> Option<String> {
+        match self {
+            Self::InvalidField(field, _) => Some(field.clone()),
+            Self::Rgb(e) => Some(e.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ShiroError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WalletNotFound => write!(f, "wallet not found"),
+            Self::MediaNotFound => write!(f, "media not found"),
+            Self::WalletNotCreated => write!(f, "wallet should be created first"),
+            Self::WalletAlreadyCreated => write!(f, "wallet already created"),
+            Self::WalletNotOnline => write!(f, "wallet should be online"),
+            Self::WatchOnly => write!(f, "not available for a watch-only wallet"),
+            Self::PassphraseNotSet => write!(f, "wallet passphrase should be set first"),
+            Self::WrongPassphrase => write!(f, "wrong passphrase"),
+            Self::Unauthorized => write!(f, "missing or invalid API token"),
+            Self::Forbidden(message) => write!(f, "{}", message),
+            Self::TooManyRequests => write!(f, "too many attempts, try again later"),
+            Self::Electrum(details) => write!(f, "Electrum server error: {}", details),
+            Self::InvalidParams(message) => write!(f, "{}", message),
+            Self::InvalidField(field, message) => write!(f, "{}: {}", field, message),
+            Self::Internal(message) => write!(f, "{}", message),
+            Self::Rgb(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<rgb_lib::Error> for ShiroError {
+    fn from(e: rgb_lib::Error) -> ShiroError {
+        ShiroError::Rgb(e)
+    }
+}
+
+impl ResponseError for ShiroError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::WalletNotFound | Self::MediaNotFound => StatusCode::NOT_FOUND,
+            Self::WalletNotCreated | Self::WalletNotOnline | Self::PassphraseNotSet => {
+                StatusCode::PRECONDITION_FAILED
+            }
+            Self::WalletAlreadyCreated => StatusCode::CONFLICT,
+            Self::WatchOnly | Self::WrongPassphrase | Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            Self::Electrum(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::InvalidParams(_) | Self::InvalidField(_, _) => StatusCode::BAD_REQUEST,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Rgb(e) => rgb_error_code(e).1,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut builder = HttpResponse::build(self.status_code());
+        if let Self::Unauthorized = self {
+            builder.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+        builder.json(ErrorResponse {
+            code: self.code(),
+            message: self.to_string(),
+            details: self.details(),
+        })
+    }
+}
+
+/// Reports malformed request bodies with the same JSON shape as the handlers
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .error_handler(|err, _req| ShiroError::InvalidParams(err.to_string()).into())
+}
+
+/// Same as [`json_config`] for multipart forms
+pub fn multipart_form_config() -> MultipartFormConfig {
+    MultipartFormConfig::default()
+        .error_handler(|err, _req| ShiroError::InvalidParams(err.to_string()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rgb_error() {
+        let e = ShiroError::from(rgb_lib::Error::AssetNotFound {
+            asset_id: "rgb1".to_string(),
+        });
+        assert_eq!(e.code(), "ASSET_NOT_FOUND");
+        assert_eq!(e.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(e.details(), Some(e.to_string()));
+
+        let e = ShiroError::from(rgb_lib::Error::Offline);
+        assert_eq!(e.code(), "OFFLINE");
+        assert_eq!(e.status_code(), StatusCode::PRECONDITION_FAILED);
+
+        let e = ShiroError::from(rgb_lib::Error::InsufficientAllocationSlots);
+        assert_eq!(e.code(), "INSUFFICIENT_ALLOCATION_SLOTS");
+        assert_eq!(e.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn test_precondition_errors() {
+        assert_eq!(
+            ShiroError::WalletAlreadyCreated.status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ShiroError::WalletNotOnline.status_code(),
+            StatusCode::PRECONDITION_FAILED
+        );
+        assert_eq!(ShiroError::WalletNotOnline.code(), "WALLET_NOT_ONLINE");
+    }
+}
@@ -1,4 +1,5 @@
-use actix_web::{post, put, web, HttpResponse, Responder};
+use crate::error::ShiroError;
+use actix_web::{post, put, web, HttpResponse, Responder, ResponseError};
 use rgb_lib::keys::{generate_keys, restore_keys};
 use serde::Deserialize;
 use serde::Serialize;
@@ -28,7 +29,9 @@ pub async fn post(params: web::Json<KeyRestoreParams>) -> impl Responder {
             xpub: keys.xpub,
             xpub_fingerprint: keys.xpub_fingerprint,
         }),
-        Result::Err(_) => HttpResponse::BadRequest().body("Invalid mnemonic"),
+        Result::Err(_) => {
+            ShiroError::InvalidParams("Invalid mnemonic".to_string()).error_response()
+        }
     }
 }
 
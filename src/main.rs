@@ -1,20 +1,166 @@
-use crate::wallet::ShiroWallet;
+use crate::auth::{ApiToken, Authentication};
+use crate::wallet::{data::mnemonic::RevealLimiter, ShiroWallet};
+use crate::wallets::{WalletRegistry, WalletSelector};
 use actix_cors::Cors;
 use actix_web::{http::header, web, App, HttpServer};
 use std::sync::Mutex;
+use std::time::Duration;
 
+mod audit;
+mod auth;
+mod error;
 mod healthz;
 mod keys;
+mod tls;
 mod wallet;
+mod wallets;
+
+/// The default wallet and the ones of the registry, named for the logs.
+fn all_wallets(
+    data: &web::Data<Mutex<ShiroWallet>>,
+    registry: &WalletRegistry,
+) -> Vec<(String, web::Data<Mutex<ShiroWallet>>)> {
+    let mut wallets = vec![("the default wallet".to_string(), data.clone())];
+    wallets.extend(
+        registry
+            .list()
+            .into_iter()
+            .map(|(wallet_id, shiro_wallet)| (format!("wallet {}", wallet_id), shiro_wallet)),
+    );
+    wallets
+}
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    HttpServer::new(|| {
-        let shiro_wallet = Mutex::new(wallet::ShiroWallet::new());
-        let data = web::Data::new(shiro_wallet);
-        let cors = Cors::default()
-            .allow_any_origin()
-            .send_wildcard()
+    let args = shiro_backend::opts::get_args();
+    let static_dir = args.get_static_dir();
+    let mut shiro_wallet = ShiroWallet::new();
+    shiro_wallet.reopen_wallets = args.reopen_wallets;
+    let data = web::Data::new(Mutex::new(shiro_wallet));
+    let registry = web::Data::new(WalletRegistry::new(
+        shiro_backend::opts::get_wallet_data().data_dir,
+        args.reopen_wallets,
+    ));
+    if args.reopen_wallets {
+        let result = data.lock().unwrap().reopen(args.skip_consistency_check);
+        match result {
+            Ok(true) => println!("the default wallet has been reopened"),
+            Ok(false) => {}
+            Err(e) => println!("failed to reopen the default wallet: {}", e),
+        }
+        registry.reopen(args.skip_consistency_check);
+    }
+    if let Some(seconds) = args.auto_lock_seconds {
+        let timeout = Duration::from_secs(seconds);
+        let data = data.clone();
+        let registry = registry.clone();
+        actix_web::rt::spawn(async move {
+            let mut interval =
+                actix_web::rt::time::interval(Duration::from_secs(seconds.clamp(1, 10)));
+            loop {
+                interval.tick().await;
+                if wallet::lock::lock_if_idle(&data, timeout) {
+                    println!("the default wallet has been locked");
+                }
+                for (wallet_id, shiro_wallet) in registry.list() {
+                    if wallet::lock::lock_if_idle(&shiro_wallet, timeout) {
+                        println!("wallet {} has been locked", wallet_id);
+                    }
+                }
+            }
+        });
+    }
+    if let Some(seconds) = args.fail_expired_transfers_seconds {
+        let data = data.clone();
+        let registry = registry.clone();
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(Duration::from_secs(seconds.max(1)));
+            loop {
+                interval.tick().await;
+                for (name, shiro_wallet) in all_wallets(&data, &registry) {
+                    let result = match actix_web::rt::task::spawn_blocking(move || {
+                        wallet::transfers::fail::fail_expired(&shiro_wallet)
+                    })
+                    .await
+                    {
+                        Ok(result) => result,
+                        Err(e) => {
+                            println!(
+                                "failing the expired transfers of {} has panicked: {}",
+                                name, e
+                            );
+                            continue;
+                        }
+                    };
+                    match result {
+                        Some(Ok(true)) => {
+                            println!("expired transfers of {} have been failed", name)
+                        }
+                        Some(Err(e)) => {
+                            println!("failed to fail the expired transfers of {}: {}", name, e)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        });
+    }
+    if let Some(seconds) = args.auto_refresh_seconds {
+        let data = data.clone();
+        let registry = registry.clone();
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(Duration::from_secs(seconds.max(1)));
+            loop {
+                interval.tick().await;
+                for (name, shiro_wallet) in all_wallets(&data, &registry) {
+                    let status = match actix_web::rt::task::spawn_blocking(move || {
+                        wallet::refresh::refresh_pending(&shiro_wallet)
+                    })
+                    .await
+                    {
+                        Ok(status) => status,
+                        Err(e) => {
+                            println!("the refresh of {} has panicked: {}", name, e);
+                            continue;
+                        }
+                    };
+                    if let Some(status) = status {
+                        match status.error {
+                            Some(e) => println!("failed to refresh {}: {}", name, e),
+                            None if status.transfers_changed => {
+                                println!("transfers of {} have been refreshed", name)
+                            }
+                            None => {}
+                        }
+                    }
+                }
+            }
+        });
+    }
+    let api_tokens = args
+        .api_tokens
+        .iter()
+        .map(|x| x.parse::<ApiToken>())
+        .collect::<Result<Vec<ApiToken>, String>>()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    if api_tokens.is_empty() {
+        if !args.insecure_no_auth {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "no API token has been given: set --api-token (or SHIRO_API_TOKENS) to {scope}:{token}, \
+                 or --insecure-no-auth (or SHIRO_INSECURE_NO_AUTH=true) to serve the API without \
+                 authentication",
+            ));
+        }
+        println!("--insecure-no-auth is set, the API is not authenticated");
+    }
+    let authentication = Authentication::new(api_tokens);
+    let reveal_limiter = web::Data::new(RevealLimiter::new());
+    let allowed_origins = args.allowed_origins.clone();
+    let server = HttpServer::new(move || {
+        let cors = allowed_origins
+            .iter()
+            .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
             .allowed_methods(vec!["GET", "DELETE", "OPTIONS", "POST", "PUT"])
             .allowed_headers(vec![
                 header::AUTHORIZATION,
@@ -25,10 +171,15 @@ async fn main() -> std::io::Result<()> {
             ])
             .max_age(3600);
 
-        let frontend = actix_files::Files::new("/", "./app").index_file("index.html");
-
-        App::new()
-            .app_data(data)
+        let app = App::new()
+            .app_data(error::json_config())
+            .app_data(error::multipart_form_config())
+            .app_data(data.clone())
+            .app_data(registry.clone())
+            .app_data(web::Data::new(authentication.clone()))
+            .app_data(reveal_limiter.clone())
+            .wrap(WalletSelector)
+            .wrap(authentication.clone())
             .wrap(cors)
             .service(healthz::get)
             .service(keys::post)
@@ -36,24 +187,63 @@ async fn main() -> std::io::Result<()> {
             .service(wallet::address::get)
             .service(wallet::invoice::put)
             .service(wallet::asset_balance::get)
+            .service(wallet::assets::get)
+            .service(wallet::assets::media::get)
+            .service(wallet::assets::media::get_by_digest)
             .service(wallet::assets::put)
+            .service(wallet::backup::post)
             .service(wallet::blind::put)
+            .service(wallet::btc::send::post)
+            .service(wallet::btc::send::end::post)
+            .service(wallet::btc_balance::get)
             .service(wallet::data::get)
+            .service(wallet::data::mnemonic::post)
             .service(wallet::dir::get)
+            .service(wallet::events::get)
             .service(wallet::drain_to::put)
+            .service(wallet::drain_to::end::put)
+            .service(wallet::fees::get)
             .service(wallet::go_online::put)
             .service(wallet::issue::rgb20::put)
+            .service(wallet::issue::rgb25::put)
+            .service(wallet::lock::post)
+            .service(wallet::passphrase::put)
+            .service(wallet::psbt::sign::post)
             .service(wallet::refresh::post)
+            .service(wallet::refresh::status::get)
+            .service(wallet::restore::post)
+            .service(wallet::transactions::get)
             .service(wallet::send::post)
+            .service(wallet::send::begin::post)
+            .service(wallet::send::end::post)
             .service(wallet::put)
+            .service(wallet::close)
             .service(wallet::transfers::delete)
             .service(wallet::transfers::put)
+            .service(wallet::transfers::fail::post)
+            .service(wallet::unlock::post)
             .service(wallet::unspents::put)
             .service(wallet::utxos::put)
-            .service(frontend)
-    })
-    .bind("0.0.0.0:8080")?
-    .workers(1)
+            .service(wallet::utxos::begin::put)
+            .service(wallet::utxos::end::put)
+            .service(wallets::get)
+            .service(wallets::delete);
+
+        match &static_dir {
+            Some(dir) => app.service(actix_files::Files::new("/", dir).index_file("index.html")),
+            None => app,
+        }
+    });
+    let server = match args.workers {
+        Some(workers) => server.workers(workers),
+        None => server,
+    };
+
+    let addr = (args.listen_addr.as_str(), args.port);
+    match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => server.bind_rustls(addr, tls::load_config(cert, key)?)?,
+        _ => server.bind(addr)?,
+    }
     .run()
     .await
 }
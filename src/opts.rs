@@ -47,6 +47,79 @@ pub struct Args {
 
     #[arg(long, default_value_t = true)]
     pub skip_consistency_check: bool,
+
+    /// Address the HTTP server listens on
+    #[arg(env = "SHIRO_LISTEN_ADDR", long, default_value = "0.0.0.0")]
+    pub listen_addr: String,
+
+    /// Port the HTTP server listens on
+    #[arg(env = "SHIRO_PORT", long, default_value_t = 8080)]
+    pub port: u16,
+
+    /// Number of HTTP worker threads (defaults to the number of physical CPU cores)
+    #[arg(env = "SHIRO_WORKERS", long)]
+    pub workers: Option<usize>,
+
+    /// Directory of the frontend served at `/`
+    #[arg(env = "SHIRO_STATIC_DIR", long, default_value = "./app")]
+    pub static_dir: String,
+
+    /// Don't serve the frontend
+    #[arg(long)]
+    pub disable_frontend: bool,
+
+    /// Path to the PEM encoded TLS certificate chain
+    #[arg(env = "SHIRO_TLS_CERT", long, requires = "tls_key")]
+    pub tls_cert: Option<String>,
+
+    /// Path to the PEM encoded TLS private key
+    #[arg(env = "SHIRO_TLS_KEY", long, requires = "tls_cert")]
+    pub tls_key: Option<String>,
+
+    /// API token as {scope}:{token}, scope being read, receive, send or admin.
+    /// Can be repeated. At least one is required, unless --insecure-no-auth is set.
+    #[arg(env = "SHIRO_API_TOKENS", long = "api-token", value_delimiter = ',')]
+    pub api_tokens: Vec<String>,
+
+    /// Serve the API without authentication when no --api-token is given
+    #[arg(env = "SHIRO_INSECURE_NO_AUTH", long)]
+    pub insecure_no_auth: bool,
+
+    /// Origin allowed to call the API from a browser. Can be repeated.
+    #[arg(
+        env = "SHIRO_ALLOWED_ORIGINS",
+        long = "allowed-origin",
+        value_delimiter = ','
+    )]
+    pub allowed_origins: Vec<String>,
+
+    /// Lock wallets that have a passphrase after this many seconds without requests
+    #[arg(env = "SHIRO_AUTO_LOCK_SECONDS", long)]
+    pub auto_lock_seconds: Option<u64>,
+
+    /// Refresh the pending transfers of online wallets every this many seconds
+    #[arg(env = "SHIRO_AUTO_REFRESH_SECONDS", long)]
+    pub auto_refresh_seconds: Option<u64>,
+
+    /// Fail the expired pending receives of online wallets every this many seconds
+    #[arg(env = "SHIRO_FAIL_EXPIRED_TRANSFERS_SECONDS", long)]
+    pub fail_expired_transfers_seconds: Option<u64>,
+
+    /// Remember created wallets in data_dir and reopen them at startup.
+    /// The mnemonic is never written in plaintext: a hot wallet is only remembered once it
+    /// has a passphrase and comes back locked. Watch-only wallets are reopened directly.
+    #[arg(env = "SHIRO_REOPEN_WALLETS", long)]
+    pub reopen_wallets: bool,
+}
+
+impl Args {
+    pub fn get_static_dir(&self) -> Option<String> {
+        if self.disable_frontend {
+            None
+        } else {
+            Some(self.static_dir.clone())
+        }
+    }
 }
 
 pub fn get_args() -> Args {
@@ -149,6 +222,45 @@ pub fn get_wallet_data() -> rgb_lib::wallet::WalletData {
 #[cfg(test)]
 mod tests {
     use super::parser::*;
+    use super::Args;
+    use clap::Parser;
+
+    #[test]
+    fn test_args_server_defaults() {
+        let args = Args::parse_from(["shiro-backend"]);
+        assert_eq!(args.listen_addr, "0.0.0.0");
+        assert_eq!(args.port, 8080);
+        assert_eq!(args.workers, None);
+        assert_eq!(args.get_static_dir(), Some("./app".to_string()));
+        assert!(args.tls_cert.is_none());
+        assert!(args.tls_key.is_none());
+        assert!(args.api_tokens.is_empty());
+        assert!(!args.insecure_no_auth);
+        assert!(args.allowed_origins.is_empty());
+    }
+
+    #[test]
+    fn test_args_api_tokens() {
+        let args = Args::parse_from([
+            "shiro-backend",
+            "--api-token",
+            "read:abc",
+            "--api-token",
+            "admin:def,send:ghi",
+        ]);
+        assert_eq!(args.api_tokens, vec!["read:abc", "admin:def", "send:ghi"]);
+    }
+
+    #[test]
+    fn test_args_disable_frontend() {
+        let args = Args::parse_from(["shiro-backend", "--disable-frontend"]);
+        assert_eq!(args.get_static_dir(), None);
+    }
+
+    #[test]
+    fn test_args_tls_requires_key() {
+        assert!(Args::try_parse_from(["shiro-backend", "--tls-cert", "cert.pem"]).is_err());
+    }
 
     #[test]
     fn test_database_type() {
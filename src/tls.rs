@@ -0,0 +1,31 @@
+use rustls::{Certificate, PrivateKey, ServerConfig};
+use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use std::fs::File;
+use std::io::{BufReader, Error, ErrorKind, Result};
+
+fn read_private_key(path: &str) -> Result<PrivateKey> {
+    let mut keys = pkcs8_private_keys(&mut BufReader::new(File::open(path)?))?;
+    if keys.is_empty() {
+        keys = rsa_private_keys(&mut BufReader::new(File::open(path)?))?;
+    }
+    match keys.into_iter().next() {
+        Some(key) => Ok(PrivateKey(key)),
+        None => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("no private key found in {}", path),
+        )),
+    }
+}
+
+pub fn load_config(cert_path: &str, key_path: &str) -> Result<ServerConfig> {
+    let cert_chain = certs(&mut BufReader::new(File::open(cert_path)?))?
+        .into_iter()
+        .map(Certificate)
+        .collect::<Vec<Certificate>>();
+    let key = read_private_key(key_path)?;
+    ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, key)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e))
+}
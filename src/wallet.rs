@@ -1,28 +1,60 @@
-use actix_web::{put, web, HttpResponse, Responder};
+use crate::error::ShiroError;
+use crate::wallet::{
+    events::{EventBus, WalletEvent},
+    refresh::RefreshStatus,
+};
+use actix_web::{post, put, web, HttpResponse, Responder, ResponseError};
 use rgb_lib::wallet::{Online, Wallet, WalletData};
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
+use std::time::Instant;
 
 pub mod address;
 pub mod asset_balance;
 pub mod assets;
+pub mod backup;
 pub mod blind;
+pub mod btc;
+pub mod btc_balance;
 pub mod data;
 pub mod dir;
 pub mod drain_to;
+pub mod dry_run;
+pub mod events;
+pub mod fees;
 pub mod go_online;
 pub mod invoice;
 pub mod issue;
+pub mod keystore;
+pub mod lock;
+pub mod passphrase;
+pub mod psbt;
+pub mod record;
 pub mod refresh;
+pub mod restore;
 pub mod send;
+pub mod transactions;
 pub mod transfers;
+pub mod unlock;
 pub mod unspents;
 pub mod utxos;
 
 pub struct ShiroWallet {
     pub wallet: Option<Wallet>,
     pub online: Option<Online>,
+    /// Overrides the `data_dir` given on the command line
+    pub data_dir: Option<String>,
+    /// Last time a request used this wallet, for auto-locking
+    pub last_used: Instant,
+    /// Kept in memory only, see `GET /wallet/refresh/status`
+    pub last_refresh: Option<RefreshStatus>,
+    /// Changes noticed after each refresh, see `GET /wallet/events`
+    pub events: EventBus,
+    /// Keep a record to reopen the wallet at startup, see `--reopen-wallets`
+    pub reopen_wallets: bool,
+    /// Electrum server the wallet was online with when locked, to go online again on unlock
+    pub locked_electrum_url: Option<String>,
 }
 
 impl ShiroWallet {
@@ -30,6 +62,49 @@ impl ShiroWallet {
         ShiroWallet {
             wallet: None,
             online: None,
+            data_dir: None,
+            last_used: Instant::now(),
+            last_refresh: None,
+            events: EventBus::new(),
+            reopen_wallets: false,
+            locked_electrum_url: None,
+        }
+    }
+
+    pub fn with_data_dir(data_dir: String) -> ShiroWallet {
+        ShiroWallet {
+            wallet: None,
+            online: None,
+            data_dir: Some(data_dir),
+            last_used: Instant::now(),
+            last_refresh: None,
+            events: EventBus::new(),
+            reopen_wallets: false,
+            locked_electrum_url: None,
+        }
+    }
+
+    pub fn get_data_dir(&self) -> String {
+        match &self.data_dir {
+            Some(data_dir) => data_dir.clone(),
+            None => shiro_backend::opts::get_wallet_data().data_dir,
+        }
+    }
+
+    /// Drops the wallet from memory, it stays on disk to be unlocked or created again.
+    pub fn lock(&mut self) {
+        if self.wallet.is_some() {
+            self.locked_electrum_url = self.online.take().map(|online| online.electrum_url);
+        }
+        self.online = None;
+        self.wallet = None;
+    }
+
+    /// Whether the wallet has been created without a mnemonic
+    pub fn is_watch_only(&self) -> bool {
+        match &self.wallet {
+            Some(wallet) => wallet.get_wallet_data().mnemonic.is_none(),
+            None => false,
         }
     }
 
@@ -37,15 +112,56 @@ impl ShiroWallet {
     pub fn get_online(&mut self) -> Option<Online> {
         self.online.clone()
     }
+
+    /// The wallet, checked again by the blocking closures since another worker may
+    /// have closed or locked it after the handler checked it.
+    pub fn wallet_mut(&mut self) -> Result<&mut Wallet, ShiroError> {
+        self.wallet.as_mut().ok_or(ShiroError::WalletNotCreated)
+    }
+
+    /// The online wallet, see [`ShiroWallet::wallet_mut`].
+    pub fn online_wallet_mut(&mut self) -> Result<(Online, &mut Wallet), ShiroError> {
+        let wallet = self.wallet.as_mut().ok_or(ShiroError::WalletNotCreated)?;
+        let online = self.online.clone().ok_or(ShiroError::WalletNotOnline)?;
+        Ok((online, wallet))
+    }
+}
+
+/// `spawn_blocking` for the handlers: a panic is answered as an internal error
+/// instead of being propagated to the worker.
+pub async fn blocking<T, F>(f: F) -> Result<T, ShiroError>
+where
+    F: FnOnce() -> Result<T, ShiroError> + Send + 'static,
+    T: Send + 'static,
+{
+    match actix_web::rt::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) => Err(ShiroError::Internal(e.to_string())),
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct WalletParams {
-    mnemonic: String,
+    /// Omit to create a watch-only wallet from the xpub alone
+    #[serde(default)]
+    mnemonic: Option<String>,
     pubkey: String,
 }
 
-#[derive(Deserialize, Serialize)]
+/// Answer to `PUT /wallet`, without the mnemonic
+#[derive(Serialize, Deserialize)]
+pub struct WalletResult {
+    pubkey: String,
+    watch_only: bool,
+}
+
+/// Returned instead of broadcasting when the wallet can't sign by itself
+#[derive(Serialize, Deserialize)]
+pub struct UnsignedPsbtResult {
+    pub unsigned_psbt: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct Balance {
     settled: String,
     future: String,
@@ -62,34 +178,57 @@ impl From<rgb_lib::wallet::Balance> for Balance {
     }
 }
 
-#[allow(clippy::await_holding_lock)]
 #[put("/wallet")]
 pub async fn put(
     params: web::Json<WalletParams>,
     data: web::Data<Mutex<ShiroWallet>>,
 ) -> impl Responder {
-    let mut shiro_wallet = data.lock().unwrap();
-    match shiro_wallet.wallet {
-        Some(_) => HttpResponse::BadRequest().body("wallet already created"),
-        None => {
-            let base_data = shiro_backend::opts::get_wallet_data();
-            let wallet_data = WalletData {
-                data_dir: base_data.data_dir,
-                bitcoin_network: base_data.bitcoin_network,
-                database_type: base_data.database_type,
-                pubkey: params.pubkey.clone(),
-                mnemonic: Some(params.mnemonic.clone()),
-            };
-            match actix_web::rt::task::spawn_blocking(move || Wallet::new(wallet_data).unwrap())
-                .await
-            {
-                Ok(wallet) => {
+    if data.lock().unwrap().wallet.is_some() {
+        return ShiroError::WalletAlreadyCreated.error_response();
+    }
+    let base_data = shiro_backend::opts::get_wallet_data();
+    let wallet_data = WalletData {
+        data_dir: data.lock().unwrap().get_data_dir(),
+        bitcoin_network: base_data.bitcoin_network,
+        database_type: base_data.database_type,
+        pubkey: params.pubkey.clone(),
+        mnemonic: params.mnemonic.clone(),
+    };
+    match blocking(move || Wallet::new(wallet_data).map_err(ShiroError::from)).await {
+        Ok(wallet) => {
+            // Another worker may have created the wallet while this one was blocked.
+            let mut shiro_wallet = data.lock().unwrap();
+            match shiro_wallet.wallet {
+                Some(_) => ShiroError::WalletAlreadyCreated.error_response(),
+                None => {
                     shiro_wallet.wallet = Some(wallet);
-                    HttpResponse::Ok().json(params)
+                    if let Err(e) = shiro_wallet.save_record() {
+                        println!("failed to save the wallet record: {}", e);
+                    }
+                    HttpResponse::Ok().json(WalletResult {
+                        pubkey: params.pubkey.clone(),
+                        watch_only: params.mnemonic.is_none(),
+                    })
                 }
-                Err(err) => HttpResponse::BadRequest().body(format!("{}", err)),
             }
         }
+        Err(e) => e.error_response(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CloseResult {}
+
+#[post("/wallet/close")]
+pub async fn close(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
+    let mut shiro_wallet = data.lock().unwrap();
+    if shiro_wallet.wallet.is_some() {
+        shiro_wallet.events.notify(WalletEvent::Closed);
+    }
+    shiro_wallet.lock();
+    match shiro_wallet.save_record() {
+        Ok(_) => HttpResponse::Ok().json(CloseResult {}),
+        Err(e) => ShiroError::Internal(e.to_string()).error_response(),
     }
 }
 
@@ -155,7 +294,7 @@ mod tests {
         )
         .await;
         let wallet_params = WalletParams {
-            mnemonic: "".to_string(),
+            mnemonic: Some("".to_string()),
             pubkey: "".to_string(),
         };
         let req = test::TestRequest::put()
@@ -177,7 +316,7 @@ mod tests {
         )
         .await;
         let wallet_params = WalletParams {
-            mnemonic: "save call film frog usual market noodle hope stomach chat word worry bad".to_string(),
+            mnemonic: Some("save call film frog usual market noodle hope stomach chat word worry bad".to_string()),
             pubkey: "tpubD6NzVbkrYhZ4YT9CY6kBTU8xYWq2GQPq4NYzaJer1CRrffVLwzYt5Rs3WhjZJGKaNaiN42JfgtnyGwHXc5n5oPbAUSbxwuwDqZci5kdAZHb".to_string(),
         };
         let req = test::TestRequest::put()
@@ -187,6 +326,20 @@ mod tests {
         let resp = test::call_service(&app, req).await;
         println!("{:?}", resp);
         assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        // The worker is still serving requests.
+        let keys = rgb_lib::generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+        let wallet_params = WalletParams {
+            mnemonic: Some(keys.mnemonic),
+            pubkey: keys.xpub,
+        };
+        let req = test::TestRequest::put()
+            .uri("/wallet")
+            .set_json(wallet_params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
     }
 
     #[actix_web::test]
@@ -199,7 +352,7 @@ mod tests {
         )
         .await;
         let wallet_params = WalletParams {
-            mnemonic: "save call film frog usual market noodle hope stomach chat word worry".to_string(),
+            mnemonic: Some("save call film frog usual market noodle hope stomach chat word worry".to_string()),
             pubkey: "tpubD6NzVbkrYhZ4YT9CY6kBTU8xYWq2GQPq4NYzaJer1CRrffVLwzYt5Rs3WhjZJGKaNaiN42JfgtnyGwHXc5n5oPbAUSbxwuwDqZci5kdAZHb".to_string(),
         };
         let req = test::TestRequest::put()
@@ -210,4 +363,70 @@ mod tests {
         println!("{:?}", resp);
         assert!(resp.status().is_success());
     }
+
+    #[actix_web::test]
+    async fn test_put_shared_between_workers() {
+        let data = web::Data::new(Mutex::new(ShiroWallet::new()));
+        let worker1 = test::init_service(App::new().app_data(data.clone()).service(put)).await;
+        let worker2 = test::init_service(
+            App::new()
+                .app_data(data.clone())
+                .service(put)
+                .service(address::get),
+        )
+        .await;
+
+        let keys = rgb_lib::generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+        let wallet_params = WalletParams {
+            mnemonic: Some(keys.mnemonic),
+            pubkey: keys.xpub,
+        };
+        let req = test::TestRequest::put()
+            .uri("/wallet")
+            .set_json(&wallet_params)
+            .to_request();
+        let resp = test::call_service(&worker1, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+
+        let req = test::TestRequest::get().uri("/wallet/address").to_request();
+        let resp = test::call_service(&worker2, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+
+        let req = test::TestRequest::put()
+            .uri("/wallet")
+            .set_json(&wallet_params)
+            .to_request();
+        let resp = test::call_service(&worker2, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    async fn test_put_watch_only() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let data = web::Data::new(shiro_wallet);
+        let app = test::init_service(
+            App::new()
+                .app_data(data.clone())
+                .service(put)
+                .service(address::get),
+        )
+        .await;
+        let keys = rgb_lib::generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+        let req = test::TestRequest::put()
+            .uri("/wallet")
+            .set_json(serde_json::json!({ "pubkey": keys.xpub }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+        assert!(data.lock().unwrap().is_watch_only());
+
+        let req = test::TestRequest::get().uri("/wallet/address").to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+    }
 }
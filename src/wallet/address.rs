@@ -1,5 +1,5 @@
-use crate::ShiroWallet;
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::{error::ShiroError, ShiroWallet};
+use actix_web::{get, web, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
@@ -20,7 +20,7 @@ pub async fn get(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
                 new_address: address,
             })
         }
-        None => HttpResponse::BadRequest().body("wallet should be created first"),
+        None => ShiroError::WalletNotCreated.error_response(),
     }
 }
 
@@ -42,7 +42,7 @@ mod tests {
         .await;
 
         let wallet_params = crate::wallet::WalletParams {
-            mnemonic: "save call film frog usual market noodle hope stomach chat word worry".to_string(),
+            mnemonic: Some("save call film frog usual market noodle hope stomach chat word worry".to_string()),
             pubkey: "tpubD6NzVbkrYhZ4YT9CY6kBTU8xYWq2GQPq4NYzaJer1CRrffVLwzYt5Rs3WhjZJGKaNaiN42JfgtnyGwHXc5n5oPbAUSbxwuwDqZci5kdAZHb".to_string(),
         };
         let wallet_req = test::TestRequest::put()
@@ -1,5 +1,9 @@
-use crate::{wallet::Balance, ShiroWallet};
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, Balance},
+    ShiroWallet,
+};
+use actix_web::{get, web, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
@@ -15,22 +19,19 @@ pub async fn get(
     data: web::Data<Mutex<ShiroWallet>>,
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
-        match actix_web::rt::task::spawn_blocking(move || {
-            data.lock()
-                .unwrap()
-                .wallet
-                .as_mut()
-                .unwrap()
-                .get_asset_balance(params.asset_id.clone())
+        match blocking(move || {
+            let mut shiro_wallet = data.lock().unwrap();
+            Ok(shiro_wallet
+                .wallet_mut()?
+                .get_asset_balance(params.asset_id.clone())?)
         })
         .await
-        .unwrap()
         {
             Ok(balance) => HttpResponse::Ok().json(Balance::from(balance)),
-            Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+            Err(e) => e.error_response(),
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
@@ -66,7 +67,7 @@ mod tests {
         {
             let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
             let params = crate::wallet::WalletParams {
-                mnemonic: keys.mnemonic,
+                mnemonic: Some(keys.mnemonic),
                 pubkey: keys.xpub,
             };
             let req = test::TestRequest::put()
@@ -1,10 +1,16 @@
-use crate::{wallet::Balance, ShiroWallet};
-use actix_web::{put, web, HttpResponse, Responder};
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, Balance},
+    ShiroWallet,
+};
+use actix_web::{get, put, web, HttpResponse, Responder, ResponseError};
 use rgb_lib::wallet::AssetIface;
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
 
+pub mod media;
+
 #[derive(Deserialize, Serialize)]
 pub struct AssetsParams {
     filter_asset_types: Vec<AssetIface>,
@@ -14,11 +20,14 @@ pub struct AssetsParams {
 pub struct Media {
     file_path: String,
     mime: String,
+    /// Served at `/wallet/assets/{asset_id}/media/{digest}`
+    digest: String,
 }
 
 impl From<rgb_lib::wallet::Media> for Media {
     fn from(x: rgb_lib::wallet::Media) -> Media {
         Media {
+            digest: media::digest_of(&x.file_path),
             file_path: x.file_path,
             mime: x.mime,
         }
@@ -107,24 +116,85 @@ pub async fn put(
     data: web::Data<Mutex<ShiroWallet>>,
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
-        match actix_web::rt::task::spawn_blocking(move || {
-            data.lock()
-                .unwrap()
-                .wallet
-                .as_mut()
+        match blocking(move || {
+            Ok(data
+                .lock()
                 .unwrap()
-                .list_assets(params.filter_asset_types.clone())
+                .wallet_mut()?
+                .list_assets(params.filter_asset_types.clone())?)
         })
         .await
-        .unwrap()
         {
             Ok(assets) => HttpResponse::Ok().json(AssetsResult {
                 assets: Assets::from(assets),
             }),
-            Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+            Err(e) => e.error_response(),
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct AssetMetadata {
+    asset_id: String,
+    asset_iface: AssetIface,
+    ticker: Option<String>,
+    name: String,
+    precision: u8,
+    issued_supply: String,
+    timestamp: i64,
+    description: Option<String>,
+    media: Vec<Media>,
+}
+
+/// Details of one asset, also of assets only known from incoming transfers.
+#[get("/wallet/assets/{asset_id}")]
+pub async fn get(
+    asset_id: web::Path<String>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            let asset_id = asset_id.into_inner();
+            match blocking(move || {
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                let metadata = wallet.get_asset_metadata(online, asset_id.clone())?;
+                // Media are only attached to RGB25 assets.
+                let media = match metadata.asset_iface {
+                    AssetIface::RGB25 => wallet
+                        .list_assets(vec![AssetIface::RGB25])?
+                        .rgb25
+                        .unwrap_or_default()
+                        .into_iter()
+                        .find(|asset| asset.asset_id == asset_id)
+                        .map(|asset| asset.data_paths)
+                        .unwrap_or_default(),
+                    AssetIface::RGB20 => vec![],
+                };
+                Ok(AssetMetadata {
+                    asset_id,
+                    asset_iface: metadata.asset_iface,
+                    ticker: metadata.ticker,
+                    name: metadata.name,
+                    precision: metadata.precision,
+                    issued_supply: metadata.issued_supply.to_string(),
+                    timestamp: metadata.timestamp,
+                    description: metadata.description,
+                    media: media.into_iter().map(Media::from).collect::<Vec<Media>>(),
+                })
+            })
+            .await
+            {
+                Ok(metadata) => HttpResponse::Ok().json(metadata),
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
@@ -132,6 +202,13 @@ pub async fn put(
 mod tests {
     use super::*;
 
+    use crate::wallet::{
+        address::AddressResult,
+        go_online::GoOnlineParams,
+        issue::rgb20::{Rgb20Params, Rgb20Result},
+        tests::{fund_wallet, gen_fake_ticker},
+        utxos::UtxosParams,
+    };
     use actix_web::{test, web, App};
     use rgb_lib::generate_keys;
 
@@ -149,7 +226,7 @@ mod tests {
         {
             let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
             let wallet_params = crate::wallet::WalletParams {
-                mnemonic: keys.mnemonic,
+                mnemonic: Some(keys.mnemonic),
                 pubkey: keys.xpub,
             };
             let req = test::TestRequest::put()
@@ -173,4 +250,101 @@ mod tests {
             assert!(resp.status().is_success());
         }
     }
+
+    #[actix_web::test]
+    async fn test_get_metadata() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(get)
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::utxos::put)
+                .service(crate::wallet::go_online::put)
+                .service(crate::wallet::issue::rgb20::put),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let wallet_params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(wallet_params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address);
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = UtxosParams::new(false, Some(1), None, 1.0);
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let ticker = gen_fake_ticker();
+        let rgb20_result: Rgb20Result = {
+            let params = Rgb20Params {
+                ticker: ticker.clone(),
+                name: "Fake Monacoin".to_string(),
+                presision: 8,
+                amounts: vec![100.to_string(), 50.to_string()],
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/issue/rgb20")
+                .set_json(params)
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+        assert_eq!(rgb20_result.precision, 8);
+        assert_eq!(rgb20_result.presision, 8);
+        {
+            let req = test::TestRequest::get()
+                .uri(&format!("/wallet/assets/{}", rgb20_result.asset_id))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let metadata: AssetMetadata = test::read_body_json(resp).await;
+            assert_eq!(metadata.ticker, Some(ticker));
+            assert_eq!(metadata.name, "Fake Monacoin");
+            assert_eq!(metadata.precision, 8);
+            assert_eq!(metadata.issued_supply, "150");
+            assert!(metadata.media.is_empty());
+        }
+        {
+            let req = test::TestRequest::get()
+                .uri("/wallet/assets/unknown")
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_client_error());
+        }
+    }
 }
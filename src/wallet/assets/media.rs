@@ -0,0 +1,295 @@
+use crate::{error::ShiroError, wallet::blocking, ShiroWallet};
+use actix_files::NamedFile;
+use actix_web::http::header::{
+    self, ContentDisposition, DispositionParam, DispositionType, HeaderValue,
+};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder, ResponseError};
+use rgb_lib::wallet::AssetIface;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Name of a media file in the wallet directory, rgb-lib names them after their digest.
+pub fn digest_of(file_path: &str) -> String {
+    Path::new(file_path)
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Media of an RGB25 asset of the wallet, the first one when `digest` is `None`.
+async fn find_media(
+    data: web::Data<Mutex<ShiroWallet>>,
+    asset_id: String,
+    digest: Option<String>,
+) -> Result<rgb_lib::wallet::Media, ShiroError> {
+    if data.lock().unwrap().wallet.is_none() {
+        return Err(ShiroError::WalletNotCreated);
+    }
+    let assets = blocking(move || {
+        let mut shiro_wallet = data.lock().unwrap();
+        Ok(shiro_wallet
+            .wallet_mut()?
+            .list_assets(vec![AssetIface::RGB25])?)
+    })
+    .await?;
+    let asset = assets
+        .rgb25
+        .unwrap_or_default()
+        .into_iter()
+        .find(|asset| asset.asset_id == asset_id)
+        .ok_or_else(|| rgb_lib::Error::AssetNotFound {
+            asset_id: asset_id.clone(),
+        })?;
+    asset
+        .data_paths
+        .into_iter()
+        .find(|media| match &digest {
+            Some(digest) => digest_of(&media.file_path) == *digest,
+            None => true,
+        })
+        .ok_or(ShiroError::MediaNotFound)
+}
+
+/// Images a browser renders without running anything, unlike SVG.
+fn is_raster_image(mime: &str) -> bool {
+    matches!(
+        mime.split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str(),
+        "image/png" | "image/jpeg" | "image/gif" | "image/webp" | "image/avif" | "image/bmp"
+    )
+}
+
+/// Media come from the issuer of the asset, a counterparty that isn't trusted: it is kept
+/// from running on the origin of the API, and only raster images are shown inline.
+async fn serve(req: &HttpRequest, media: rgb_lib::wallet::Media, immutable: bool) -> HttpResponse {
+    let mut file = match NamedFile::open_async(&media.file_path).await {
+        Ok(file) => file,
+        Err(_) => return ShiroError::MediaNotFound.error_response(),
+    };
+    if !is_raster_image(&media.mime) {
+        file = file.set_content_disposition(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(digest_of(&media.file_path))],
+        });
+    }
+    let mut resp = file
+        .use_etag(true)
+        .use_last_modified(true)
+        .into_response(req);
+    if let Ok(mime) = HeaderValue::from_str(&media.mime) {
+        resp.headers_mut().insert(header::CONTENT_TYPE, mime);
+    }
+    resp.headers_mut().insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    resp.headers_mut().insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("sandbox"),
+    );
+    // A digest always designates the same content, while the first media of an asset may change.
+    let cache_control = if immutable {
+        "private, max-age=31536000, immutable"
+    } else {
+        "private, no-cache"
+    };
+    resp.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(cache_control),
+    );
+    resp
+}
+
+#[get("/wallet/assets/{asset_id}/media")]
+pub async fn get(
+    req: HttpRequest,
+    asset_id: web::Path<String>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    match find_media(data, asset_id.into_inner(), None).await {
+        Ok(media) => serve(&req, media, false).await,
+        Err(e) => e.error_response(),
+    }
+}
+
+#[get("/wallet/assets/{asset_id}/media/{digest}")]
+pub async fn get_by_digest(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    let (asset_id, digest) = path.into_inner();
+    match find_media(data, asset_id, Some(digest)).await {
+        Ok(media) => serve(&req, media, true).await,
+        Err(e) => e.error_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::{
+        address::AddressResult,
+        go_online::GoOnlineParams,
+        issue::rgb25::tests::{issue_request, multipart_body},
+        tests::fund_wallet,
+        utxos::UtxosParams,
+    };
+    use actix_web::{http, test, App};
+    use rgb_lib::generate_keys;
+
+    #[test]
+    fn test_digest_of() {
+        assert_eq!(
+            digest_of("/tmp/shiro-wallet/abcd/media_files/5f3a9e"),
+            "5f3a9e"
+        );
+        assert_eq!(digest_of(""), "");
+    }
+
+    #[test]
+    fn test_is_raster_image() {
+        assert!(is_raster_image("image/png"));
+        assert!(is_raster_image("IMAGE/JPEG; charset=binary"));
+        assert!(!is_raster_image("image/svg+xml"));
+        assert!(!is_raster_image("text/html"));
+        assert!(!is_raster_image(""));
+    }
+
+    #[actix_web::test]
+    async fn test_get() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(get)
+                .service(get_by_digest)
+                .service(crate::wallet::issue::rgb25::put)
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::utxos::put)
+                .service(crate::wallet::go_online::put),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address.clone());
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = UtxosParams::new(false, Some(1), None, 1.0);
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let asset: serde_json::Value = {
+            let body = multipart_body(
+                &[
+                    ("name", "Fake Collectible"),
+                    ("precision", "0"),
+                    ("amounts", "10"),
+                ],
+                Some(b"fake media"),
+            );
+            let resp = test::call_service(&app, issue_request(body).to_request()).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/wallet/assets/{}/media",
+                asset["asset_id"].as_str().unwrap()
+            ))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            asset["data_paths"][0]["mime"].as_str().unwrap()
+        );
+        assert_eq!(
+            resp.headers().get(header::X_CONTENT_TYPE_OPTIONS).unwrap(),
+            "nosniff"
+        );
+        assert_eq!(
+            resp.headers().get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            "sandbox"
+        );
+        // A text file is downloaded rather than shown.
+        assert!(resp
+            .headers()
+            .get(header::CONTENT_DISPOSITION)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("attachment"));
+        assert_eq!(test::read_body(resp).await, "fake media".as_bytes());
+
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/wallet/assets/{}/media/{}",
+                asset["asset_id"].as_str().unwrap(),
+                asset["data_paths"][0]["digest"].as_str().unwrap()
+            ))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+        assert!(resp
+            .headers()
+            .get(header::CACHE_CONTROL)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("immutable"));
+
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/wallet/assets/{}/media/unknown",
+                asset["asset_id"].as_str().unwrap()
+            ))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{error::ShiroError, wallet::blocking, ShiroWallet};
+use actix_files::NamedFile;
+use actix_web::http::header::{
+    self, ContentDisposition, DispositionParam, DispositionType, HeaderValue,
+};
+use actix_web::{post, web, HttpRequest, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize)]
+pub struct BackupParams {
+    /// The archive is encrypted with a key derived from it
+    password: String,
+}
+
+/// Checks the password of a backup or a restore, rgb-lib accepts an empty one.
+pub fn check_password(password: &str) -> Result<(), ShiroError> {
+    if password.is_empty() {
+        return Err(ShiroError::InvalidField(
+            "password".to_string(),
+            "should not be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Downloads an encrypted archive of the wallet directory (consignments, media and database),
+/// to be given back to `POST /wallet/restore`. The mnemonic is not part of it.
+#[post("/wallet/backup")]
+pub async fn post(
+    req: HttpRequest,
+    params: web::Json<BackupParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if let Err(e) = check_password(&params.password) {
+            return e.error_response();
+        }
+        match blocking(move || {
+            let mut shiro_wallet = data.lock().unwrap();
+            let wallet = shiro_wallet.wallet_mut()?;
+            let fingerprint = wallet
+                .get_wallet_dir()
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let backup_path = std::env::temp_dir().join(format!(
+                "shiro-backup-{}",
+                hex::encode(rand::random::<[u8; 8]>())
+            ));
+            let result = wallet
+                .backup(&backup_path.to_string_lossy(), &params.password)
+                .map_err(ShiroError::from)
+                .and_then(|_| {
+                    fs::File::open(&backup_path).map_err(|e| ShiroError::Internal(e.to_string()))
+                });
+            // Unlinked once opened, the archive is gone when the response has been streamed.
+            let _ = fs::remove_file(&backup_path);
+            let file = NamedFile::from_file(result?, &backup_path)
+                .map_err(|e| ShiroError::Internal(e.to_string()))?;
+            Ok(file.set_content_disposition(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(format!(
+                    "{}.backup",
+                    fingerprint
+                ))],
+            }))
+        })
+        .await
+        {
+            Ok(file) => {
+                let mut resp = file.into_response(&req);
+                resp.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/octet-stream"),
+                );
+                resp
+            }
+            Err(e) => e.error_response(),
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tests::PROXY_ENDPOINT;
+    use crate::wallet::{
+        address::AddressResult,
+        blind::BlindParams,
+        data::WalletDataResponse,
+        go_online::GoOnlineParams,
+        restore::{tests::restore_request, RestoreResult},
+        tests::fund_wallet,
+        utxos::UtxosParams,
+    };
+    use actix_web::{http, test, App};
+    use rgb_lib::generate_keys;
+    use std::path::Path;
+
+    #[actix_web::test]
+    async fn test_backup_and_restore() {
+        let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+        let wallet_params = crate::wallet::WalletParams {
+            mnemonic: Some(keys.mnemonic.clone()),
+            pubkey: keys.xpub.clone(),
+        };
+        let root = std::env::temp_dir().join(format!(
+            "shiro-backup-test-{}",
+            hex::encode(rand::random::<[u8; 4]>())
+        ));
+
+        let archive = {
+            let data_dir = root.join("original").to_string_lossy().to_string();
+            fs::create_dir_all(&data_dir).unwrap();
+            let app = test::init_service(
+                App::new()
+                    .app_data(web::Data::new(Mutex::new(ShiroWallet::with_data_dir(
+                        data_dir,
+                    ))))
+                    .service(post)
+                    .service(crate::wallet::data::get)
+                    .service(crate::wallet::put)
+                    .service(crate::wallet::address::get)
+                    .service(crate::wallet::go_online::put)
+                    .service(crate::wallet::utxos::put)
+                    .service(crate::wallet::blind::put),
+            )
+            .await;
+            let app_ref = &app;
+            let backup_required = || async move {
+                let req = test::TestRequest::get().uri("/wallet/data").to_request();
+                let resp = test::call_service(app_ref, req).await;
+                println!("{:?}", resp);
+                assert!(resp.status().is_success());
+                let body: WalletDataResponse = test::read_body_json(resp).await;
+                body.backup_required
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(&wallet_params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+
+            let address: AddressResult = {
+                let req = test::TestRequest::get().uri("/wallet/address").to_request();
+                test::call_and_read_body_json(&app, req).await
+            };
+            fund_wallet(address.new_address);
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(GoOnlineParams::new(true, "127.0.0.1:50001".to_string()))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos")
+                .set_json(UtxosParams::new(true, Some(1), None, 1.0))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            assert!(backup_required().await);
+
+            let req = test::TestRequest::post()
+                .uri("/wallet/backup")
+                .set_json(BackupParams {
+                    password: "".to_string(),
+                })
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+            let req = test::TestRequest::post()
+                .uri("/wallet/backup")
+                .set_json(BackupParams {
+                    password: "correct horse".to_string(),
+                })
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let archive = test::read_body(resp).await.to_vec();
+            assert!(!backup_required().await);
+
+            // A new blinded UTXO makes the archive outdated.
+            let req = test::TestRequest::put()
+                .uri("/wallet/blind")
+                .set_json(BlindParams::new(vec![PROXY_ENDPOINT.clone()]))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            assert!(backup_required().await);
+            archive
+        };
+        assert!(!archive.is_empty());
+
+        let data_dir = root.join("restored").to_string_lossy().to_string();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Mutex::new(ShiroWallet::with_data_dir(
+                    data_dir.clone(),
+                ))))
+                .service(crate::wallet::restore::post)
+                .service(crate::wallet::put),
+        )
+        .await;
+
+        let req = restore_request("wrong horse", None, &archive).to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+        // The archive of another wallet is refused.
+        let req = restore_request("correct horse", Some("00000000"), &archive).to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+        assert!(!Path::new(&data_dir).join(&keys.xpub_fingerprint).exists());
+
+        let req =
+            restore_request("correct horse", Some(&keys.xpub_fingerprint), &archive).to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+        let result: RestoreResult = test::read_body_json(resp).await;
+        assert_eq!(result.fingerprint, keys.xpub_fingerprint);
+        assert!(Path::new(&data_dir).join(&keys.xpub_fingerprint).is_dir());
+
+        // The restored directory is picked up by the wallet.
+        let req = test::TestRequest::put()
+            .uri("/wallet")
+            .set_json(&wallet_params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+
+        // A loaded wallet can't be overwritten.
+        let req = restore_request("correct horse", None, &archive).to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::CONFLICT);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,5 +1,5 @@
-use crate::ShiroWallet;
-use actix_web::{put, web, HttpResponse, Responder};
+use crate::{error::ShiroError, wallet::blocking, ShiroWallet};
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
@@ -19,14 +19,24 @@ pub struct BlindParamsForLib {
     transport_endpoints: Vec<String>,
 }
 
-impl From<BlindParams> for BlindParamsForLib {
-    fn from(x: BlindParams) -> BlindParamsForLib {
-        BlindParamsForLib {
+impl TryFrom<BlindParams> for BlindParamsForLib {
+    type Error = ShiroError;
+
+    fn try_from(x: BlindParams) -> Result<BlindParamsForLib, ShiroError> {
+        let amount = x
+            .amount
+            .map(|amount| {
+                amount
+                    .parse::<u64>()
+                    .map_err(|_| ShiroError::InvalidParams(format!("invalid amount: {:?}", amount)))
+            })
+            .transpose()?;
+        Ok(BlindParamsForLib {
             asset_id: x.asset_id,
-            amount: x.amount.map(|str| str.parse::<u64>().unwrap()),
+            amount,
             duration_seconds: x.duration_seconds,
             transport_endpoints: x.transport_endpoints,
-        }
+        })
     }
 }
 
@@ -55,23 +65,25 @@ pub async fn put(
     data: web::Data<Mutex<ShiroWallet>>,
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
-        match actix_web::rt::task::spawn_blocking(move || {
-            let params = BlindParamsForLib::from(params.clone());
-            data.lock().unwrap().wallet.as_mut().unwrap().blind(
+        let params = match BlindParamsForLib::try_from(params.into_inner()) {
+            Ok(params) => params,
+            Err(e) => return e.error_response(),
+        };
+        match blocking(move || {
+            Ok(data.lock().unwrap().wallet_mut()?.blind(
                 params.asset_id.clone(),
                 params.amount,
                 params.duration_seconds,
                 params.transport_endpoints,
-            )
+            )?)
         })
         .await
-        .unwrap()
         {
             Ok(blind_data) => HttpResponse::Ok().json(BlindData::from(blind_data)),
-            Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+            Err(e) => e.error_response(),
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
@@ -87,9 +99,20 @@ mod tests {
         tests::fund_wallet,
         utxos::UtxosParams,
     };
-    use actix_web::{test, web, App};
+    use actix_web::{http, test, web, App};
     use rgb_lib::generate_keys;
 
+    impl BlindParams {
+        pub fn new(transport_endpoints: Vec<String>) -> BlindParams {
+            BlindParams {
+                asset_id: None,
+                amount: None,
+                duration_seconds: None,
+                transport_endpoints,
+            }
+        }
+    }
+
     #[actix_web::test]
     async fn test_put() {
         let shiro_wallet = Mutex::new(ShiroWallet::new());
@@ -108,7 +131,7 @@ mod tests {
         {
             let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
             let params = crate::wallet::WalletParams {
-                mnemonic: keys.mnemonic,
+                mnemonic: Some(keys.mnemonic),
                 pubkey: keys.xpub,
             };
             let req = test::TestRequest::put()
@@ -174,4 +197,51 @@ mod tests {
         println!("{:?}", resp);
         assert!(resp.status().is_success());
     }
+
+    #[actix_web::test]
+    async fn test_put_bad_amount() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(put),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let params = BlindParams {
+            asset_id: None,
+            amount: Some("ten".to_string()),
+            duration_seconds: None,
+            transport_endpoints: vec![PROXY_ENDPOINT.clone()],
+        };
+        let req = test::TestRequest::put()
+            .uri("/wallet/blind")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        // The worker is still serving requests.
+        let req = test::TestRequest::get().uri("/wallet/address").to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+    }
 }
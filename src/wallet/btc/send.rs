@@ -0,0 +1,210 @@
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, fees::FeeRate, UnsignedPsbtResult},
+    ShiroWallet,
+};
+use actix_web::{post, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+pub mod end;
+
+#[derive(Serialize, Deserialize)]
+pub struct BtcSendParams {
+    address: String,
+    /// In satoshis
+    amount: String,
+    fee_rate: FeeRate,
+}
+
+pub struct BtcSendParamsForLib {
+    address: String,
+    amount: u64,
+    fee_rate: FeeRate,
+}
+
+impl TryFrom<BtcSendParams> for BtcSendParamsForLib {
+    type Error = ShiroError;
+
+    fn try_from(x: BtcSendParams) -> Result<BtcSendParamsForLib, ShiroError> {
+        let amount = match x.amount.parse::<u64>() {
+            Ok(amount) if amount > 0 => amount,
+            _ => {
+                return Err(ShiroError::InvalidField(
+                    "amount".to_string(),
+                    format!("invalid amount: {:?}", x.amount),
+                ))
+            }
+        };
+        Ok(BtcSendParamsForLib {
+            address: x.address,
+            amount,
+            fee_rate: x.fee_rate,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BtcSendResult {
+    txid: String,
+}
+
+/// Pays `amount` to `address` from the vanilla wallet, colored UTXOs are never spent.
+#[post("/wallet/btc/send")]
+pub async fn post(
+    params: web::Json<BtcSendParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            let params = match BtcSendParamsForLib::try_from(params.into_inner()) {
+                Ok(params) => params,
+                Err(e) => return e.error_response(),
+            };
+            let watch_only = data.lock().unwrap().is_watch_only();
+            match blocking(move || {
+                let fee_rate = params.fee_rate.resolve_for(&data)?;
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                let result = if watch_only {
+                    wallet.send_btc_begin(online, params.address, params.amount, fee_rate)
+                } else {
+                    wallet.send_btc(online, params.address, params.amount, fee_rate)
+                };
+                Ok(result?)
+            })
+            .await
+            {
+                Ok(unsigned_psbt) if watch_only => {
+                    HttpResponse::Ok().json(UnsignedPsbtResult { unsigned_psbt })
+                }
+                Ok(txid) => HttpResponse::Ok().json(BtcSendResult { txid }),
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::{
+        address::AddressResult, fees::FeePreset, go_online::GoOnlineParams, tests::fund_wallet,
+        unspents::UnspentsResult, utxos::UtxosParams,
+    };
+    use actix_web::{http, test, App};
+    use rgb_lib::generate_keys;
+
+    #[actix_web::test]
+    async fn test_post() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(post)
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::utxos::put)
+                .service(crate::wallet::unspents::put)
+                .service(crate::wallet::go_online::put),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address.clone());
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = UtxosParams::new(false, Some(1), None, 1.0);
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let colorable_before = {
+            let req = test::TestRequest::put()
+                .uri("/wallet/unspents")
+                .set_json(serde_json::json!({ "settled_only": false }))
+                .to_request();
+            let result: UnspentsResult = test::call_and_read_body_json(&app, req).await;
+            result.unspents.iter().filter(|x| x.utxo.colorable).count()
+        };
+        {
+            let params = BtcSendParams {
+                address: address.new_address.clone(),
+                amount: "abc".to_string(),
+                fee_rate: FeeRate::Rate(1.0),
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/btc/send")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+        }
+        {
+            let params = BtcSendParams {
+                address: address.new_address,
+                amount: "10000".to_string(),
+                fee_rate: FeeRate::Preset(FeePreset::Slow),
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/btc/send")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let result: BtcSendResult = test::read_body_json(resp).await;
+            assert!(!result.txid.is_empty());
+        }
+        {
+            // The colored UTXOs created above are left untouched.
+            let req = test::TestRequest::put()
+                .uri("/wallet/unspents")
+                .set_json(serde_json::json!({ "settled_only": false }))
+                .to_request();
+            let result: UnspentsResult = test::call_and_read_body_json(&app, req).await;
+            let colorable = result.unspents.iter().filter(|x| x.utxo.colorable).count();
+            assert_eq!(colorable, colorable_before);
+        }
+    }
+}
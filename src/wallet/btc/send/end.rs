@@ -0,0 +1,148 @@
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, btc::send::BtcSendResult},
+    ShiroWallet,
+};
+use actix_web::{post, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize)]
+pub struct BtcSendEndParams {
+    signed_psbt: String,
+}
+
+/// Broadcasts the PSBT answered by `POST /wallet/btc/send` to a watch-only wallet, once signed.
+#[post("/wallet/btc/send/end")]
+pub async fn post(
+    params: web::Json<BtcSendEndParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            match blocking(move || {
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                Ok(wallet.send_btc_end(online, params.signed_psbt.clone())?)
+            })
+            .await
+            {
+                Ok(txid) => HttpResponse::Ok().json(BtcSendResult { txid }),
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::{
+        address::AddressResult, btc::send::BtcSendParams, fees::FeeRate, go_online::GoOnlineParams,
+        tests::fund_wallet, UnsignedPsbtResult,
+    };
+    use actix_web::{test, App};
+    use rgb_lib::{
+        generate_keys,
+        wallet::{Wallet, WalletData},
+    };
+
+    #[actix_web::test]
+    async fn test_post_watch_only() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::go_online::put)
+                .service(crate::wallet::btc::send::post)
+                .service(post),
+        )
+        .await;
+
+        let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+        {
+            let params = crate::wallet::WalletParams {
+                mnemonic: None,
+                pubkey: keys.xpub.clone(),
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address.clone());
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let begin_result: UnsignedPsbtResult = {
+            let params = BtcSendParams {
+                address: address.new_address,
+                amount: "10000".to_string(),
+                fee_rate: FeeRate::Rate(1.0),
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/btc/send")
+                .set_json(params)
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+
+        // Signed outside the server, by a wallet holding the mnemonic.
+        let signer_dir = std::env::temp_dir().join(format!(
+            "shiro-signer-{}",
+            hex::encode(rand::random::<[u8; 4]>())
+        ));
+        std::fs::create_dir_all(&signer_dir).unwrap();
+        let base_data = shiro_backend::opts::get_wallet_data();
+        let wallet_data = WalletData {
+            data_dir: signer_dir.to_string_lossy().to_string(),
+            bitcoin_network: base_data.bitcoin_network,
+            database_type: base_data.database_type,
+            pubkey: keys.xpub,
+            mnemonic: Some(keys.mnemonic),
+        };
+        let signed_psbt = actix_web::rt::task::spawn_blocking(move || {
+            let wallet = Wallet::new(wallet_data).unwrap();
+            wallet.sign_psbt(begin_result.unsigned_psbt).unwrap()
+        })
+        .await
+        .unwrap();
+        std::fs::remove_dir_all(signer_dir).unwrap();
+
+        let params = BtcSendEndParams { signed_psbt };
+        let req = test::TestRequest::post()
+            .uri("/wallet/btc/send/end")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+        let result: BtcSendResult = test::read_body_json(resp).await;
+        assert!(!result.txid.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, Balance},
+    ShiroWallet,
+};
+use actix_web::{get, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct BtcBalance {
+    /// Bitcoins on UTXOs that can't hold RGB allocations
+    vanilla: Balance,
+    /// Bitcoins on the UTXOs used for RGB allocations
+    colored: Balance,
+}
+
+impl From<rgb_lib::wallet::BtcBalance> for BtcBalance {
+    fn from(x: rgb_lib::wallet::BtcBalance) -> BtcBalance {
+        BtcBalance {
+            vanilla: Balance::from(x.vanilla),
+            colored: Balance::from(x.colored),
+        }
+    }
+}
+
+#[get("/wallet/btc_balance")]
+pub async fn get(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            match blocking(move || {
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                Ok(wallet.get_btc_balance(online)?)
+            })
+            .await
+            {
+                Ok(balance) => HttpResponse::Ok().json(BtcBalance::from(balance)),
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::{address::AddressResult, go_online::GoOnlineParams, tests::fund_wallet};
+    use actix_web::{http, test, App};
+    use rgb_lib::generate_keys;
+
+    #[actix_web::test]
+    async fn test_get() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(get)
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::go_online::put),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let req = test::TestRequest::get()
+                .uri("/wallet/btc_balance")
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::PRECONDITION_FAILED);
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address);
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let req = test::TestRequest::get()
+                .uri("/wallet/btc_balance")
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let balance: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(balance["vanilla"]["future"], "100000000");
+        }
+    }
+}
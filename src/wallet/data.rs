@@ -1,11 +1,13 @@
-use crate::wallet::ShiroWallet;
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::{error::ShiroError, wallet::ShiroWallet};
+use actix_web::{get, web, HttpResponse, Responder, ResponseError};
 use rgb_lib::wallet::DatabaseType;
 use rgb_lib::BitcoinNetwork;
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
 
+pub mod mnemonic;
+
 #[derive(Serialize, Deserialize)]
 pub struct WalletDataResponse {
     /// Directory where the wallet directory is to be created
@@ -16,8 +18,10 @@ pub struct WalletDataResponse {
     pub database_type: String,
     /// Wallet xpub
     pub pubkey: String,
-    /// Wallet mnemonic phrase
-    pub mnemonic: String,
+    /// Whether the wallet has no mnemonic, see `POST /wallet/data/mnemonic` to reveal it
+    pub watch_only: bool,
+    /// Whether the wallet state changed since the last `POST /wallet/backup`
+    pub backup_required: bool,
 }
 
 #[get("/wallet/data")]
@@ -25,6 +29,10 @@ pub async fn get(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
     let shiro_wallet = data.lock().unwrap();
     match &shiro_wallet.wallet {
         Some(wallet) => {
+            let backup_required = match wallet.backup_info() {
+                Ok(backup_required) => backup_required,
+                Err(e) => return ShiroError::from(e).error_response(),
+            };
             let wdata = wallet.get_wallet_data();
             HttpResponse::Ok().json(WalletDataResponse {
                 data_dir: wdata.data_dir.clone(),
@@ -40,14 +48,11 @@ pub async fn get(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
                 }
                 .to_string(),
                 pubkey: wdata.pubkey.clone(),
-                mnemonic: if let Some(mnemonic) = &wdata.mnemonic {
-                    mnemonic.clone()
-                } else {
-                    "".to_string()
-                },
+                watch_only: wdata.mnemonic.is_none(),
+                backup_required,
             })
         }
-        None => HttpResponse::BadRequest().body("wallet doesn't be initialized"),
+        None => ShiroError::WalletNotCreated.error_response(),
     }
 }
 
@@ -69,7 +74,7 @@ mod tests {
         )
         .await;
         let wallet_params = crate::wallet::WalletParams {
-            mnemonic: "".to_string(),
+            mnemonic: Some("".to_string()),
             pubkey: "".to_string(),
         };
         let wallet_req = test::TestRequest::put()
@@ -85,7 +90,9 @@ mod tests {
         let resp = test::call_service(&app, req).await;
         println!("{:?}", resp);
 
-        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+        assert_eq!(resp.status(), http::StatusCode::PRECONDITION_FAILED);
+        let body: crate::error::ErrorResponse = test::read_body_json(resp).await;
+        assert_eq!(body.code, "WALLET_NOT_CREATED");
     }
 
     #[actix_web::test]
@@ -99,7 +106,7 @@ mod tests {
         )
         .await;
         let wallet_params = crate::wallet::WalletParams {
-            mnemonic: "save call film frog usual market noodle hope stomach chat word worry".to_string(),
+            mnemonic: Some("save call film frog usual market noodle hope stomach chat word worry".to_string()),
             pubkey: "xpub661MyMwAqRbcGexM5um6FYobDPjNH1tmWjxhDkbhfHfxvNpdsmhnvzCDGfemmmNLagBTSSno9nxvaknvDDvqux8sQqrfGPGzFc2JKnf4KL9".to_string(),
         };
         let wallet_req = test::TestRequest::put()
@@ -121,9 +128,15 @@ mod tests {
         assert_eq!(body.bitcoin_network, "regtest");
         assert_eq!(body.database_type, "sqlite");
         assert_eq!(body.pubkey, "xpub661MyMwAqRbcGexM5um6FYobDPjNH1tmWjxhDkbhfHfxvNpdsmhnvzCDGfemmmNLagBTSSno9nxvaknvDDvqux8sQqrfGPGzFc2JKnf4KL9");
-        assert_eq!(
-            body.mnemonic,
-            "save call film frog usual market noodle hope stomach chat word worry"
-        );
+        assert!(!body.watch_only);
+        let body = test::read_body(
+            test::call_service(
+                &app,
+                test::TestRequest::get().uri("/wallet/data").to_request(),
+            )
+            .await,
+        )
+        .await;
+        assert!(!String::from_utf8_lossy(&body).contains("save call film"));
     }
 }
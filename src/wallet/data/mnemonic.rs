@@ -0,0 +1,229 @@
+use crate::{
+    auth::Authentication,
+    error::ShiroError,
+    wallet::{blocking, keystore::Keystore},
+    ShiroWallet,
+};
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const MAX_ATTEMPTS: usize = 5;
+const ATTEMPTS_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Limits how often the mnemonic can be asked for, whatever the outcome.
+/// Attempts are counted for each wallet, by its `data_dir`, and peer address.
+pub struct RevealLimiter {
+    attempts: Mutex<HashMap<(String, String), VecDeque<Instant>>>,
+}
+
+impl RevealLimiter {
+    pub fn new() -> RevealLimiter {
+        RevealLimiter {
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn try_attempt(&self, data_dir: &str, peer: Option<&str>) -> bool {
+        let now = Instant::now();
+        let mut attempts = self.attempts.lock().unwrap();
+        attempts.retain(|_, times| {
+            while let Some(first) = times.front() {
+                if now.duration_since(*first) < ATTEMPTS_WINDOW {
+                    break;
+                }
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+        let times = attempts
+            .entry((data_dir.to_string(), peer.unwrap_or("unknown").to_string()))
+            .or_default();
+        if times.len() >= MAX_ATTEMPTS {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+}
+
+/// Credentials asked again even if the request is already authenticated
+#[derive(Serialize, Deserialize)]
+pub struct RevealParams {
+    /// An admin API token
+    #[serde(default)]
+    token: Option<String>,
+    /// The wallet passphrase, see `PUT /wallet/passphrase`
+    #[serde(default)]
+    passphrase: Option<String>,
+}
+
+impl RevealParams {
+    fn is_authorized(&self, authentication: &Authentication, data_dir: &str) -> bool {
+        if let Some(token) = &self.token {
+            if authentication.is_admin_token(token) {
+                return true;
+            }
+        }
+        match (&self.passphrase, Keystore::load(data_dir)) {
+            (Some(passphrase), Ok(Some(keystore))) => keystore.decrypt(passphrase).is_ok(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RevealResult {
+    mnemonic: String,
+}
+
+#[post("/wallet/data/mnemonic")]
+pub async fn post(
+    req: HttpRequest,
+    params: web::Json<RevealParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+    authentication: web::Data<Authentication>,
+    limiter: web::Data<RevealLimiter>,
+) -> impl Responder {
+    let data_dir = {
+        let shiro_wallet = data.lock().unwrap();
+        if shiro_wallet.wallet.is_none() {
+            return ShiroError::WalletNotCreated.error_response();
+        }
+        shiro_wallet.get_data_dir()
+    };
+    let peer = req.peer_addr().map(|x| x.ip().to_string());
+    // The passphrase check and the audit log don't need the wallet, it is only locked to read the
+    // mnemonic once they are done.
+    match blocking(move || {
+        if !limiter.try_attempt(&data_dir, peer.as_deref()) {
+            crate::audit::log(&data_dir, "reveal_mnemonic", peer, "rate-limited");
+            return Err(ShiroError::TooManyRequests);
+        }
+        if !params.is_authorized(&authentication, &data_dir) {
+            crate::audit::log(&data_dir, "reveal_mnemonic", peer, "denied");
+            return Err(ShiroError::Forbidden(
+                "an admin API token or the passphrase is required".to_string(),
+            ));
+        }
+        let mnemonic = match &data.lock().unwrap().wallet {
+            Some(wallet) => wallet.get_wallet_data().mnemonic,
+            None => return Err(ShiroError::WalletNotCreated),
+        };
+        match mnemonic {
+            Some(mnemonic) => {
+                crate::audit::log(&data_dir, "reveal_mnemonic", peer, "revealed");
+                Ok(RevealResult { mnemonic })
+            }
+            None => Err(ShiroError::WatchOnly),
+        }
+    })
+    .await
+    {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => e.error_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::auth::ApiToken;
+    use actix_web::{http, test, App};
+
+    #[actix_web::test]
+    async fn test_post() {
+        let tokens = vec!["admin:administrator".parse::<ApiToken>().unwrap()];
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Mutex::new(ShiroWallet::new())))
+                .app_data(web::Data::new(Authentication::new(tokens)))
+                .app_data(web::Data::new(RevealLimiter::new()))
+                .service(crate::wallet::put)
+                .service(post),
+        )
+        .await;
+
+        {
+            let wallet_params = crate::wallet::WalletParams {
+                mnemonic: Some("save call film frog usual market noodle hope stomach chat word worry".to_string()),
+                pubkey: "tpubD6NzVbkrYhZ4YT9CY6kBTU8xYWq2GQPq4NYzaJer1CRrffVLwzYt5Rs3WhjZJGKaNaiN42JfgtnyGwHXc5n5oPbAUSbxwuwDqZci5kdAZHb".to_string(),
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(wallet_params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = RevealParams {
+                token: Some("reader".to_string()),
+                passphrase: None,
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/data/mnemonic")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+        }
+        {
+            let params = RevealParams {
+                token: Some("administrator".to_string()),
+                passphrase: None,
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/data/mnemonic")
+                .set_json(params)
+                .to_request();
+            let result: RevealResult = test::call_and_read_body_json(&app, req).await;
+            assert_eq!(
+                result.mnemonic,
+                "save call film frog usual market noodle hope stomach chat word worry"
+            );
+        }
+        for _ in 0..MAX_ATTEMPTS {
+            let params = RevealParams {
+                token: Some("administrator".to_string()),
+                passphrase: None,
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/data/mnemonic")
+                .set_json(params)
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+        {
+            let params = RevealParams {
+                token: Some("administrator".to_string()),
+                passphrase: None,
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/data/mnemonic")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+        }
+    }
+
+    #[test]
+    fn test_reveal_limiter() {
+        let limiter = RevealLimiter::new();
+        for _ in 0..MAX_ATTEMPTS {
+            assert!(limiter.try_attempt("/tmp/alice", Some("10.0.0.1")));
+        }
+        assert!(!limiter.try_attempt("/tmp/alice", Some("10.0.0.1")));
+        // Neither another peer nor another wallet is locked out.
+        assert!(limiter.try_attempt("/tmp/alice", Some("10.0.0.2")));
+        assert!(limiter.try_attempt("/tmp/bob", Some("10.0.0.1")));
+    }
+}
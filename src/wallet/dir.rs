@@ -1,5 +1,5 @@
-use crate::ShiroWallet;
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::{error::ShiroError, ShiroWallet};
+use actix_web::{get, web, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
@@ -16,7 +16,7 @@ pub async fn get(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
         Some(wallet) => HttpResponse::Ok().json(WalletDir {
             wallet_dir: wallet.get_wallet_data().data_dir,
         }),
-        None => HttpResponse::BadRequest().body("wallet data has not been provided"),
+        None => ShiroError::WalletNotCreated.error_response(),
     }
 }
 
@@ -36,7 +36,7 @@ mod tests {
         )
         .await;
         let wallet_params = crate::wallet::WalletParams {
-            mnemonic: "".to_string(),
+            mnemonic: Some("".to_string()),
             pubkey: "".to_string(),
         };
         let wallet_req = test::TestRequest::put()
@@ -66,7 +66,7 @@ mod tests {
         )
         .await;
         let wallet_params = crate::wallet::WalletParams {
-            mnemonic: "save call film frog usual market noodle hope stomach chat word worry".to_string(),
+            mnemonic: Some("save call film frog usual market noodle hope stomach chat word worry".to_string()),
             pubkey: "tpubD6NzVbkrYhZ4YT9CY6kBTU8xYWq2GQPq4NYzaJer1CRrffVLwzYt5Rs3WhjZJGKaNaiN42JfgtnyGwHXc5n5oPbAUSbxwuwDqZci5kdAZHb".to_string(),
         };
         let wallet_req = test::TestRequest::put()
@@ -1,14 +1,28 @@
-use crate::ShiroWallet;
-use actix_web::{put, web, HttpResponse, Responder};
+use crate::{
+    error::ShiroError,
+    wallet::{
+        blocking,
+        dry_run::{DryRunResult, Moved},
+        fees::FeeRate,
+        UnsignedPsbtResult,
+    },
+    ShiroWallet,
+};
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
 
+pub mod end;
+
 #[derive(Serialize, Deserialize)]
 pub struct DrainToParams {
     address: String,
     destroy_assets: bool,
-    fee_rate: f32,
+    fee_rate: FeeRate,
+    /// Only return what the transaction would be, see [`DryRunResult`]
+    #[serde(default)]
+    dry_run: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -23,27 +37,66 @@ pub async fn put(
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
         if data.lock().unwrap().online.is_some() {
-            match actix_web::rt::task::spawn_blocking(move || {
+            if params.dry_run {
+                return match blocking(move || {
+                    let fee_rate = params.fee_rate.resolve_for(&data)?;
+                    let mut shiro_wallet = data.lock().unwrap();
+                    let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                    let moved = if params.destroy_assets {
+                        Moved::SpentAllocations
+                    } else {
+                        Moved::Nothing
+                    };
+                    DryRunResult::preview(wallet, &online, moved, |wallet, online| {
+                        wallet.drain_to_begin(
+                            online,
+                            params.address.clone(),
+                            params.destroy_assets,
+                            fee_rate,
+                        )
+                    })
+                })
+                .await
+                {
+                    Ok(result) => HttpResponse::Ok().json(result),
+                    Err(e) => e.error_response(),
+                };
+            }
+            let watch_only = data.lock().unwrap().is_watch_only();
+            match blocking(move || {
+                let fee_rate = params.fee_rate.resolve_for(&data)?;
                 let mut shiro_wallet = data.lock().unwrap();
-                let online = shiro_wallet.get_online().unwrap();
-                shiro_wallet.wallet.as_mut().unwrap().drain_to(
-                    online,
-                    params.address.clone(),
-                    params.destroy_assets,
-                    params.fee_rate,
-                )
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                let result = if watch_only {
+                    wallet.drain_to_begin(
+                        online,
+                        params.address.clone(),
+                        params.destroy_assets,
+                        fee_rate,
+                    )
+                } else {
+                    wallet.drain_to(
+                        online,
+                        params.address.clone(),
+                        params.destroy_assets,
+                        fee_rate,
+                    )
+                };
+                Ok(result?)
             })
             .await
-            .unwrap()
             {
+                Ok(unsigned_psbt) if watch_only => {
+                    HttpResponse::Ok().json(UnsignedPsbtResult { unsigned_psbt })
+                }
                 Ok(txid) => HttpResponse::Ok().json(DrainToResult { txid }),
-                Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+                Err(e) => e.error_response(),
             }
         } else {
-            HttpResponse::BadRequest().body("wallet should be online")
+            ShiroError::WalletNotOnline.error_response()
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
@@ -51,7 +104,15 @@ pub async fn put(
 mod tests {
     use super::*;
 
-    use crate::wallet::{address::AddressResult, go_online::GoOnlineParams, WalletParams};
+    use crate::wallet::{
+        address::AddressResult,
+        dry_run::tests::{dir_snapshot, transfers},
+        go_online::GoOnlineParams,
+        issue::rgb20::{Rgb20Params, Rgb20Result},
+        tests::{fund_wallet, gen_fake_ticker},
+        utxos::UtxosParams,
+        WalletParams,
+    };
     use actix_web::{http, test, web, App};
     use rgb_lib::generate_keys;
 
@@ -71,7 +132,7 @@ mod tests {
         {
             let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
             let wallet_params = WalletParams {
-                mnemonic: keys.mnemonic,
+                mnemonic: Some(keys.mnemonic),
                 pubkey: keys.xpub,
             };
             let wallet_req = test::TestRequest::put()
@@ -103,7 +164,8 @@ mod tests {
             let params = DrainToParams {
                 address: address.new_address,
                 destroy_assets: false,
-                fee_rate: 0.0,
+                fee_rate: FeeRate::Rate(0.0),
+                dry_run: false,
             };
             let req = test::TestRequest::put()
                 .uri("/wallet/drain_to")
@@ -115,6 +177,133 @@ mod tests {
         }
     }
 
+    #[actix_web::test]
+    async fn test_put_dry_run() {
+        let data = web::Data::new(Mutex::new(ShiroWallet::new()));
+        let app = test::init_service(
+            App::new()
+                .app_data(data.clone())
+                .service(put)
+                .service(crate::wallet::go_online::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::issue::rgb20::put)
+                .service(crate::wallet::utxos::put)
+                .service(crate::wallet::put),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let wallet_params = WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let wallet_req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(wallet_params)
+                .to_request();
+            let wallet_resp = test::call_service(&app, wallet_req).await;
+            println!("{:?}", wallet_resp);
+            assert!(wallet_resp.status().is_success());
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address.clone());
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = UtxosParams::new(true, Some(1), None, 1.0);
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let rgb20_result: Rgb20Result = {
+            let params = Rgb20Params {
+                ticker: gen_fake_ticker(),
+                name: "Fake Monacoin".to_string(),
+                presision: 7,
+                amounts: vec![666.to_string()],
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/issue/rgb20")
+                .set_json(params)
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+
+        let wallet_dir = data
+            .lock()
+            .unwrap()
+            .wallet
+            .as_ref()
+            .unwrap()
+            .get_wallet_dir();
+        let dir_before = dir_snapshot(&wallet_dir);
+        let transfers_before = transfers(&data, &rgb20_result.asset_id);
+        {
+            let params = DrainToParams {
+                address: address.new_address.clone(),
+                destroy_assets: true,
+                fee_rate: FeeRate::Rate(1.0),
+                dry_run: true,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/drain_to")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let result: DryRunResult = test::read_body_json(resp).await;
+            assert!(result.summary.fee.is_some());
+            assert_eq!(result.spent_allocations.len(), 1);
+            assert_eq!(result.spent_allocations[0].asset_id, rgb20_result.asset_id);
+            assert_eq!(result.spent_allocations[0].amount, "666");
+            assert_eq!(
+                result.balances.assets.get(&rgb20_result.asset_id),
+                Some(&"0".to_string())
+            );
+        }
+        {
+            let params = DrainToParams {
+                address: address.new_address,
+                destroy_assets: false,
+                fee_rate: FeeRate::Rate(1.0),
+                dry_run: true,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/drain_to")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let result: DryRunResult = test::read_body_json(resp).await;
+            // The colored UTXOs are kept.
+            assert!(result.spent_allocations.is_empty());
+            assert!(result.balances.assets.is_empty());
+        }
+        assert!(dir_before == dir_snapshot(&wallet_dir));
+        assert_eq!(transfers_before, transfers(&data, &rgb20_result.asset_id));
+    }
+
     #[actix_web::test]
     #[ignore]
     async fn test_put_success() {}
@@ -0,0 +1,39 @@
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, drain_to::DrainToResult},
+    ShiroWallet,
+};
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize)]
+pub struct DrainToEndParams {
+    signed_psbt: String,
+}
+
+#[put("/wallet/drain_to/end")]
+pub async fn put(
+    params: web::Json<DrainToEndParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            match blocking(move || {
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                Ok(wallet.drain_to_end(online, params.signed_psbt.clone())?)
+            })
+            .await
+            {
+                Ok(txid) => HttpResponse::Ok().json(DrainToResult { txid }),
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{error::ShiroError, wallet::psbt::PsbtSummary};
+use rgb_lib::wallet::{Online, Wallet, WalletData};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Directories of the wallet which rgb-lib only writes to while preparing a transaction: the media
+/// of the assets and the consignments of past transfers.
+const UNUSED_DIRS: [&str; 2] = ["media_files", "transfers"];
+
+/// RGB assets taken out of the wallet by the previewed transaction.
+pub enum Moved {
+    /// Only bitcoin is spent
+    Nothing,
+    /// Amounts sent to recipients, the rest of the spent allocations comes back as change
+    Amounts(BTreeMap<String, u64>),
+    /// Every allocation of the spent UTXOs, as destroyed by a drain
+    SpentAllocations,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpentAllocation {
+    pub asset_id: String,
+    pub amount: String,
+}
+
+/// Balances of the wallet once the transaction is confirmed.
+#[derive(Serialize, Deserialize)]
+pub struct ResultingBalances {
+    /// Bitcoin of the vanilla and colored wallets, unknown when the PSBT misses the spent outputs
+    pub btc: Option<String>,
+    /// Spendable balance of the assets in `spent_allocations`
+    pub assets: BTreeMap<String, String>,
+}
+
+/// Answer of the operations called with `dry_run: true`, nothing is broadcast nor saved.
+#[derive(Serialize, Deserialize)]
+pub struct DryRunResult {
+    pub summary: PsbtSummary,
+    /// RGB allocations leaving the wallet, the change coming back to it is not listed
+    pub spent_allocations: Vec<SpentAllocation>,
+    pub balances: ResultingBalances,
+}
+
+impl DryRunResult {
+    /// Runs `begin` on a throwaway copy of the wallet directory, so the transfer it prepares and
+    /// the addresses it derives never reach the wallet itself.
+    pub fn preview<F>(
+        wallet: &Wallet,
+        online: &Online,
+        moved: Moved,
+        begin: F,
+    ) -> Result<DryRunResult, ShiroError>
+    where
+        F: FnOnce(&mut Wallet, Online) -> Result<String, rgb_lib::Error>,
+    {
+        let data_dir = std::env::temp_dir().join(format!(
+            "shiro-dry-run-{}",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+        let wallet_dir = wallet.get_wallet_dir();
+        let result = copy_wallet_dir(
+            &wallet_dir,
+            &data_dir.join(wallet_dir.file_name().unwrap_or_default()),
+        )
+        .map_err(|e| ShiroError::Internal(e.to_string()))
+        .and_then(|_| {
+            let mut copy = Wallet::new(WalletData {
+                data_dir: data_dir.to_string_lossy().to_string(),
+                ..wallet.get_wallet_data()
+            })?;
+            let online = copy.go_online(true, online.electrum_url.clone())?;
+            DryRunResult::new(&mut copy, online, moved, begin)
+        });
+        let _ = fs::remove_dir_all(&data_dir);
+        result
+    }
+
+    fn new<F>(
+        wallet: &mut Wallet,
+        online: Online,
+        moved: Moved,
+        begin: F,
+    ) -> Result<DryRunResult, ShiroError>
+    where
+        F: FnOnce(&mut Wallet, Online) -> Result<String, rgb_lib::Error>,
+    {
+        let btc_balance = wallet.get_btc_balance(online.clone())?;
+        let unsigned_psbt = begin(wallet, online)?;
+        let summary = PsbtSummary::new(&unsigned_psbt, wallet.get_wallet_data().bitcoin_network)
+            .map_err(ShiroError::Internal)?;
+        let moved = match moved {
+            Moved::Nothing => BTreeMap::new(),
+            Moved::Amounts(amounts) => amounts,
+            Moved::SpentAllocations => {
+                let mut amounts = BTreeMap::new();
+                for unspent in wallet.list_unspents(false)? {
+                    let outpoint = format!(
+                        "{}:{}",
+                        unspent.utxo.outpoint.txid, unspent.utxo.outpoint.vout
+                    );
+                    if !summary
+                        .inputs
+                        .iter()
+                        .any(|input| input.outpoint == outpoint)
+                    {
+                        continue;
+                    }
+                    for allocation in unspent.rgb_allocations {
+                        if let Some(asset_id) = allocation.asset_id {
+                            *amounts.entry(asset_id).or_insert(0) += allocation.amount;
+                        }
+                    }
+                }
+                amounts
+            }
+        };
+        let mut assets = BTreeMap::new();
+        for (asset_id, amount) in &moved {
+            let balance = wallet.get_asset_balance(asset_id.clone())?;
+            assets.insert(
+                asset_id.clone(),
+                balance.spendable.saturating_sub(*amount).to_string(),
+            );
+        }
+        let btc = summary.spent().map(|spent| {
+            (btc_balance.vanilla.future + btc_balance.colored.future)
+                .saturating_sub(spent)
+                .to_string()
+        });
+        Ok(DryRunResult {
+            summary,
+            spent_allocations: moved
+                .into_iter()
+                .map(|(asset_id, amount)| SpentAllocation {
+                    asset_id,
+                    amount: amount.to_string(),
+                })
+                .collect(),
+            balances: ResultingBalances { btc, assets },
+        })
+    }
+}
+
+/// Copies what is needed to open the wallet and prepare a transaction.
+fn copy_wallet_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if entry.file_type()?.is_dir() {
+            if UNUSED_DIRS.iter().any(|dir| name == *dir) {
+                continue;
+            }
+            copy_dir(&entry.path(), &to.join(name))?;
+        } else {
+            fs::copy(entry.path(), to.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{wallet::transfers::Transfer, ShiroWallet};
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+
+    /// Transfers of an asset as answered by `PUT /wallet/transfers`.
+    pub fn transfers(data: &Mutex<ShiroWallet>, asset_id: &str) -> String {
+        let transfers = data
+            .lock()
+            .unwrap()
+            .wallet_mut()
+            .unwrap()
+            .list_transfers(asset_id.to_string())
+            .unwrap();
+        serde_json::to_string(
+            &transfers
+                .into_iter()
+                .map(Transfer::from)
+                .collect::<Vec<Transfer>>(),
+        )
+        .unwrap()
+    }
+
+    /// Content of every file of a wallet directory, to check a dry run left it alone.
+    pub fn dir_snapshot(path: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+        let mut snapshot = BTreeMap::new();
+        for entry in fs::read_dir(path).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                snapshot.extend(dir_snapshot(&path));
+            } else {
+                snapshot.insert(path.clone(), fs::read(&path).unwrap());
+            }
+        }
+        snapshot
+    }
+}
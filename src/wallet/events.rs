@@ -0,0 +1,466 @@
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, btc_balance::BtcBalance, transfers::status_name, Balance},
+    ShiroWallet,
+};
+use actix_web::http::{header, StatusCode};
+use actix_web::rt::time::timeout;
+use actix_web::{get, web, HttpResponse, Responder, ResponseError};
+use futures_util::{stream, Stream, StreamExt};
+use rgb_lib::wallet::{Online, Wallet};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Events kept for a slow client before it misses some
+const EVENT_CAPACITY: usize = 64;
+
+/// A comment is sent when nothing happened for this long, so proxies keep the stream open
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Sent as the `data` of a server-sent event, as `{"type": "...", ...}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WalletEvent {
+    NewAsset {
+        asset_id: String,
+    },
+    Balance {
+        asset_id: String,
+        balance: Balance,
+    },
+    TransferStatus {
+        asset_id: String,
+        idx: i32,
+        status: String,
+    },
+    BtcBalance {
+        balance: BtcBalance,
+    },
+    /// The wallet went online, or a refresh reached the servers again after `Offline`
+    Online {
+        electrum_url: String,
+    },
+    /// A refresh couldn't reach the Electrum server or the proxy
+    Offline {
+        error: String,
+    },
+    /// The client was too slow and missed `missed` events, the state should be fetched again
+    Lagged {
+        missed: u64,
+    },
+    /// The wallet has been closed, last event of the stream
+    Closed,
+    /// The wallet has been locked, last event of the stream
+    Locked,
+}
+
+impl WalletEvent {
+    fn ends_stream(&self) -> bool {
+        matches!(self, WalletEvent::Closed | WalletEvent::Locked)
+    }
+}
+
+/// Balances and transfer statuses of every asset, compared after each refresh.
+#[derive(Default)]
+struct Snapshot {
+    balances: BTreeMap<String, Balance>,
+    transfers: BTreeMap<(String, i32), String>,
+    /// Unknown while the wallet is offline
+    btc: Option<BtcBalance>,
+}
+
+impl Snapshot {
+    fn take(wallet: &mut Wallet, online: Option<Online>) -> Result<Snapshot, rgb_lib::Error> {
+        let assets = wallet.list_assets(vec![])?;
+        let balances = assets
+            .rgb20
+            .unwrap_or_default()
+            .into_iter()
+            .map(|asset| (asset.asset_id, asset.balance))
+            .chain(
+                assets
+                    .rgb25
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|asset| (asset.asset_id, asset.balance)),
+            )
+            .map(|(asset_id, balance)| (asset_id, Balance::from(balance)))
+            .collect::<BTreeMap<_, _>>();
+        let mut transfers = BTreeMap::new();
+        for asset_id in balances.keys() {
+            for transfer in wallet.list_transfers(asset_id.clone())? {
+                transfers.insert(
+                    (asset_id.clone(), transfer.idx),
+                    status_name(&transfer.status).to_string(),
+                );
+            }
+        }
+        let btc = match online {
+            Some(online) => Some(BtcBalance::from(wallet.get_btc_balance(online)?)),
+            None => None,
+        };
+        Ok(Snapshot {
+            balances,
+            transfers,
+            btc,
+        })
+    }
+
+    fn diff(&self, new: &Snapshot) -> Vec<WalletEvent> {
+        let mut events = vec![];
+        for (asset_id, balance) in &new.balances {
+            match self.balances.get(asset_id) {
+                Some(old) if old == balance => continue,
+                Some(_) => {}
+                None => events.push(WalletEvent::NewAsset {
+                    asset_id: asset_id.clone(),
+                }),
+            }
+            events.push(WalletEvent::Balance {
+                asset_id: asset_id.clone(),
+                balance: balance.clone(),
+            });
+        }
+        for ((asset_id, idx), status) in &new.transfers {
+            if self.transfers.get(&(asset_id.clone(), *idx)) != Some(status) {
+                events.push(WalletEvent::TransferStatus {
+                    asset_id: asset_id.clone(),
+                    idx: *idx,
+                    status: status.clone(),
+                });
+            }
+        }
+        if let Some(btc) = &new.btc {
+            if self.btc.as_ref() != Some(btc) {
+                events.push(WalletEvent::BtcBalance {
+                    balance: btc.clone(),
+                });
+            }
+        }
+        events
+    }
+}
+
+/// Publishes the changes of a wallet to the `GET /wallet/events` streams.
+pub struct EventBus {
+    sender: broadcast::Sender<WalletEvent>,
+    /// State the next refresh is compared to, only kept while someone listens
+    snapshot: Option<Snapshot>,
+    /// The last refresh sent `Offline`
+    offline: bool,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus {
+            sender: broadcast::channel(EVENT_CAPACITY).0,
+            snapshot: None,
+            offline: false,
+        }
+    }
+
+    pub fn subscribe(
+        &mut self,
+        wallet: &mut Wallet,
+        online: Option<Online>,
+    ) -> Result<broadcast::Receiver<WalletEvent>, ShiroError> {
+        if self.snapshot.is_none() {
+            self.snapshot = Some(Snapshot::take(wallet, online)?);
+        }
+        Ok(self.sender.subscribe())
+    }
+
+    /// Sends an event which doesn't come from a refresh, like going online or being closed.
+    pub fn notify(&mut self, event: WalletEvent) {
+        match event {
+            WalletEvent::Online { .. } => self.offline = false,
+            WalletEvent::Closed | WalletEvent::Locked => {
+                self.snapshot = None;
+                self.offline = false;
+            }
+            _ => {}
+        }
+        let _ = self.sender.send(event);
+    }
+
+    /// Compares the wallet to its state before the refresh which gave `result`.
+    pub fn publish(
+        &mut self,
+        wallet: &mut Wallet,
+        online: Option<Online>,
+        result: &Result<bool, ShiroError>,
+    ) {
+        if self.sender.receiver_count() == 0 {
+            self.snapshot = None;
+            return;
+        }
+        if let Err(e) = result {
+            if e.status_code() == StatusCode::SERVICE_UNAVAILABLE {
+                self.offline = true;
+                let _ = self.sender.send(WalletEvent::Offline {
+                    error: e.to_string(),
+                });
+            }
+            return;
+        }
+        if self.offline {
+            if let Some(online) = &online {
+                self.notify(WalletEvent::Online {
+                    electrum_url: online.electrum_url.clone(),
+                });
+            }
+        }
+        match Snapshot::take(wallet, online) {
+            Ok(snapshot) => {
+                if let Some(old) = self.snapshot.take() {
+                    for event in old.diff(&snapshot) {
+                        let _ = self.sender.send(event);
+                    }
+                }
+                self.snapshot = Some(snapshot);
+            }
+            Err(e) => println!("failed to list the changes of the wallet: {}", e),
+        }
+    }
+}
+
+fn to_sse(event: &WalletEvent) -> web::Bytes {
+    web::Bytes::from(format!(
+        "data: {}\n\n",
+        serde_json::to_string(event).unwrap()
+    ))
+}
+
+/// The events of `receiver` with keep-alive comments, until the wallet is closed or locked.
+fn event_stream(
+    receiver: broadcast::Receiver<WalletEvent>,
+) -> impl Stream<Item = Result<web::Bytes, actix_web::Error>> {
+    stream::unfold((receiver, false), |(mut receiver, ended)| async move {
+        if ended {
+            return None;
+        }
+        let chunk = match timeout(KEEP_ALIVE_INTERVAL, receiver.recv()).await {
+            Err(_) => web::Bytes::from_static(b": keep-alive\n\n"),
+            Ok(Ok(event)) => {
+                let ended = event.ends_stream();
+                return Some((Ok(to_sse(&event)), (receiver, ended)));
+            }
+            Ok(Err(broadcast::error::RecvError::Lagged(missed))) => {
+                to_sse(&WalletEvent::Lagged { missed })
+            }
+            Ok(Err(broadcast::error::RecvError::Closed)) => return None,
+        };
+        Some((Ok(chunk), (receiver, false)))
+    })
+}
+
+/// Server-sent events stream of the changes noticed by each refresh, manual or automatic.
+#[get("/wallet/events")]
+pub async fn get(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        let receiver = match blocking(move || {
+            let mut shiro_wallet = data.lock().unwrap();
+            let shiro_wallet = &mut *shiro_wallet;
+            let online = shiro_wallet.online.clone();
+            let wallet = shiro_wallet
+                .wallet
+                .as_mut()
+                .ok_or(ShiroError::WalletNotCreated)?;
+            shiro_wallet.events.subscribe(wallet, online)
+        })
+        .await
+        {
+            Ok(receiver) => receiver,
+            Err(e) => return e.error_response(),
+        };
+        let connected = stream::once(async {
+            Ok::<_, actix_web::Error>(web::Bytes::from_static(b": connected\n\n"))
+        });
+        HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header((header::CACHE_CONTROL, "no-cache"))
+            .streaming(connected.chain(event_stream(receiver)))
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use actix_web::body::MessageBody;
+    use actix_web::{http, test, App};
+    use futures_util::future::poll_fn;
+    use rgb_lib::generate_keys;
+    use std::pin::Pin;
+
+    fn balance(settled: u64) -> Balance {
+        Balance::from(rgb_lib::wallet::Balance {
+            settled,
+            future: settled,
+            spendable: settled,
+        })
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut old = Snapshot::default();
+        old.balances.insert("rgb1".to_string(), balance(10));
+        old.transfers
+            .insert(("rgb1".to_string(), 1), "Settled".to_string());
+        old.transfers
+            .insert(("rgb1".to_string(), 2), "WaitingCounterparty".to_string());
+
+        let mut new = Snapshot::default();
+        new.balances.insert("rgb1".to_string(), balance(10));
+        new.balances.insert("rgb2".to_string(), balance(5));
+        new.transfers
+            .insert(("rgb1".to_string(), 1), "Settled".to_string());
+        new.transfers
+            .insert(("rgb1".to_string(), 2), "WaitingConfirmations".to_string());
+
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                WalletEvent::NewAsset {
+                    asset_id: "rgb2".to_string()
+                },
+                WalletEvent::Balance {
+                    asset_id: "rgb2".to_string(),
+                    balance: balance(5)
+                },
+                WalletEvent::TransferStatus {
+                    asset_id: "rgb1".to_string(),
+                    idx: 2,
+                    status: "WaitingConfirmations".to_string()
+                },
+            ]
+        );
+        assert!(new.diff(&new).is_empty());
+
+        let btc = BtcBalance::from(rgb_lib::wallet::BtcBalance {
+            vanilla: rgb_lib::wallet::Balance {
+                settled: 1000,
+                future: 1000,
+                spendable: 1000,
+            },
+            colored: rgb_lib::wallet::Balance {
+                settled: 0,
+                future: 0,
+                spendable: 0,
+            },
+        });
+        let online = Snapshot {
+            balances: new.balances.clone(),
+            transfers: new.transfers.clone(),
+            btc: Some(btc.clone()),
+        };
+        assert_eq!(
+            new.diff(&online),
+            vec![WalletEvent::BtcBalance { balance: btc }]
+        );
+        // Going offline isn't a balance change.
+        assert!(online.diff(&new).is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_get() {
+        let data = web::Data::new(Mutex::new(ShiroWallet::new()));
+        let app = test::init_service(
+            App::new()
+                .app_data(data.clone())
+                .service(crate::wallet::put)
+                .service(crate::wallet::close)
+                .service(get),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/wallet/events").to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::PRECONDITION_FAILED);
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let req = test::TestRequest::get().uri("/wallet/events").to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+        let mut body = resp.into_body();
+        let chunk = poll_fn(|cx| Pin::new(&mut body).poll_next(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap(), ": connected\n\n");
+
+        {
+            let mut shiro_wallet = data.lock().unwrap();
+            let shiro_wallet = &mut *shiro_wallet;
+            shiro_wallet.events.publish(
+                shiro_wallet.wallet.as_mut().unwrap(),
+                None,
+                &Err(ShiroError::Electrum("unreachable".to_string())),
+            );
+        }
+        let chunk = poll_fn(|cx| Pin::new(&mut body).poll_next(cx)).await;
+        let chunk = chunk.unwrap().unwrap();
+        let event: WalletEvent = serde_json::from_slice(
+            chunk
+                .strip_prefix(b"data: ")
+                .unwrap()
+                .strip_suffix(b"\n\n")
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(event, WalletEvent::Offline { .. }));
+
+        // Closing the wallet ends the stream.
+        let req = test::TestRequest::post().uri("/wallet/close").to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+        let chunk = poll_fn(|cx| Pin::new(&mut body).poll_next(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap(), to_sse(&WalletEvent::Closed));
+        assert!(poll_fn(|cx| Pin::new(&mut body).poll_next(cx))
+            .await
+            .is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_event_stream_lagged() {
+        let mut events = EventBus::new();
+        let receiver = events.sender.subscribe();
+        for _ in 0..EVENT_CAPACITY + 3 {
+            events.notify(WalletEvent::Online {
+                electrum_url: "127.0.0.1:50001".to_string(),
+            });
+        }
+        events.notify(WalletEvent::Locked);
+
+        let chunks = event_stream(receiver)
+            .map(|chunk| chunk.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(chunks[0], to_sse(&WalletEvent::Lagged { missed: 4 }));
+        assert_eq!(chunks.len(), 1 + EVENT_CAPACITY);
+        assert_eq!(chunks.last().unwrap(), &to_sse(&WalletEvent::Locked));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{error::ShiroError, wallet::blocking, ShiroWallet};
+use actix_web::{get, web, HttpResponse, Responder, ResponseError};
+use electrum_client::{Client, ElectrumApi};
+use rgb_lib::wallet::Online;
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Used when the Electrum server has no estimate, like on a fresh regtest chain
+const MIN_FEE_RATE: f32 = 1.0;
+
+/// Confirmation targets of the presets, in blocks
+const FAST_TARGET: u16 = 1;
+const NORMAL_TARGET: u16 = 6;
+const SLOW_TARGET: u16 = 24;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeePreset {
+    Fast,
+    Normal,
+    Slow,
+}
+
+impl FeePreset {
+    fn target(&self) -> u16 {
+        match self {
+            FeePreset::Fast => FAST_TARGET,
+            FeePreset::Normal => NORMAL_TARGET,
+            FeePreset::Slow => SLOW_TARGET,
+        }
+    }
+}
+
+/// `fee_rate` of the operations: sat/vB, `"fast"`, `"normal"`, `"slow"` or `{"target_blocks": n}`
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FeeRate {
+    Rate(f32),
+    Preset(FeePreset),
+    Target { target_blocks: u16 },
+}
+
+impl FeeRate {
+    /// Asks the Electrum server of `online` unless the rate is given, blocks.
+    pub fn resolve(&self, online: &Online) -> Result<f32, ShiroError> {
+        match self {
+            FeeRate::Rate(rate) if !rate.is_finite() || *rate < MIN_FEE_RATE => {
+                Err(ShiroError::InvalidField(
+                    "fee_rate".to_string(),
+                    format!("should be at least {} sat/vB", MIN_FEE_RATE),
+                ))
+            }
+            FeeRate::Rate(rate) => Ok(*rate),
+            FeeRate::Preset(preset) => estimate(&online.electrum_url, preset.target()),
+            FeeRate::Target { target_blocks } => estimate(&online.electrum_url, *target_blocks),
+        }
+    }
+
+    /// Like [`FeeRate::resolve`] with the online wallet, the lock isn't held while Electrum answers.
+    pub fn resolve_for(&self, data: &Mutex<ShiroWallet>) -> Result<f32, ShiroError> {
+        let online = data
+            .lock()
+            .unwrap()
+            .get_online()
+            .ok_or(ShiroError::WalletNotOnline)?;
+        self.resolve(&online)
+    }
+}
+
+/// Fee rate in sat/vB to be confirmed within `target` blocks.
+pub fn estimate(electrum_url: &str, target: u16) -> Result<f32, ShiroError> {
+    if target == 0 {
+        return Err(ShiroError::InvalidField(
+            "fee_rate".to_string(),
+            "target_blocks should be at least 1".to_string(),
+        ));
+    }
+    let client = Client::new(electrum_url).map_err(|e| ShiroError::Electrum(e.to_string()))?;
+    let btc_per_kvb = client
+        .estimate_fee(target as usize)
+        .map_err(|e| ShiroError::Electrum(e.to_string()))?;
+    // Electrum answers -1 when it has no estimate.
+    let rate = (btc_per_kvb * 100_000.0) as f32;
+    Ok(rate.max(MIN_FEE_RATE))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FeesResult {
+    /// sat/vB for each preset
+    fast: f32,
+    normal: f32,
+    slow: f32,
+}
+
+#[get("/wallet/fees")]
+pub async fn get(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        let online = match data.lock().unwrap().get_online() {
+            Some(online) => online,
+            None => return ShiroError::WalletNotOnline.error_response(),
+        };
+        match blocking(move || {
+            Ok(FeesResult {
+                fast: FeeRate::Preset(FeePreset::Fast).resolve(&online)?,
+                normal: FeeRate::Preset(FeePreset::Normal).resolve(&online)?,
+                slow: FeeRate::Preset(FeePreset::Slow).resolve(&online)?,
+            })
+        })
+        .await
+        {
+            Ok(fees) => HttpResponse::Ok().json(fees),
+            Err(e) => e.error_response(),
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::go_online::GoOnlineParams;
+    use actix_web::{test, App};
+    use rgb_lib::generate_keys;
+
+    #[test]
+    fn test_fee_rate() {
+        let parse = |x: serde_json::Value| serde_json::from_value::<FeeRate>(x).ok();
+        assert!(matches!(
+            parse(serde_json::json!(1.5)),
+            Some(FeeRate::Rate(rate)) if rate == 1.5
+        ));
+        assert!(matches!(
+            parse(serde_json::json!("fast")),
+            Some(FeeRate::Preset(FeePreset::Fast))
+        ));
+        assert!(matches!(
+            parse(serde_json::json!({ "target_blocks": 3 })),
+            Some(FeeRate::Target { target_blocks: 3 })
+        ));
+        assert!(parse(serde_json::json!("instant")).is_none());
+    }
+
+    #[test]
+    fn test_resolve_invalid_rate() {
+        let online = Online {
+            id: 1,
+            electrum_url: "127.0.0.1:50001".to_string(),
+        };
+        for rate in [0.5, 0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                FeeRate::Rate(rate).resolve(&online),
+                Err(ShiroError::InvalidField(field, _)) if field == "fee_rate"
+            ));
+        }
+        assert!(matches!(FeeRate::Rate(1.0).resolve(&online), Ok(rate) if rate == 1.0));
+    }
+
+    #[actix_web::test]
+    async fn test_get() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(get)
+                .service(crate::wallet::put)
+                .service(crate::wallet::go_online::put),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let req = test::TestRequest::get().uri("/wallet/fees").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let fees: FeesResult = test::read_body_json(resp).await;
+            assert!(fees.fast >= MIN_FEE_RATE);
+            assert!(fees.slow >= MIN_FEE_RATE);
+        }
+    }
+}
@@ -1,5 +1,9 @@
-use crate::ShiroWallet;
-use actix_web::{put, web, HttpResponse, Responder};
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, events::WalletEvent},
+    ShiroWallet,
+};
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
@@ -29,22 +33,27 @@ pub async fn put(
     data: web::Data<Mutex<ShiroWallet>>,
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
-        match actix_web::rt::task::spawn_blocking(move || {
+        match blocking(move || {
             let mut shiro_wallet = data.lock().unwrap();
-            let result = shiro_wallet
-                .wallet
-                .as_mut()
-                .unwrap()
-                .go_online(params.skip_consistency_check, params.electrum_url.clone());
-            shiro_wallet.online = Some(result.unwrap())
+            let online = shiro_wallet
+                .wallet_mut()?
+                .go_online(params.skip_consistency_check, params.electrum_url.clone())?;
+            shiro_wallet.online = Some(online);
+            shiro_wallet.events.notify(WalletEvent::Online {
+                electrum_url: params.electrum_url.clone(),
+            });
+            if let Err(e) = shiro_wallet.save_record() {
+                println!("failed to save the wallet record: {}", e);
+            }
+            Ok(())
         })
         .await
         {
-            Ok(_) => HttpResponse::Ok().json(GoOnlineResult {}),
-            Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+            Ok(()) => HttpResponse::Ok().json(GoOnlineResult {}),
+            Err(e) => e.error_response(),
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
@@ -67,7 +76,7 @@ mod tests {
         .await;
 
         let wallet_params = crate::wallet::WalletParams {
-            mnemonic: "save call film frog usual market noodle hope stomach chat word worry".to_string(),
+            mnemonic: Some("save call film frog usual market noodle hope stomach chat word worry".to_string()),
             pubkey: "tpubD6NzVbkrYhZ4YT9CY6kBTU8xYWq2GQPq4NYzaJer1CRrffVLwzYt5Rs3WhjZJGKaNaiN42JfgtnyGwHXc5n5oPbAUSbxwuwDqZci5kdAZHb".to_string(),
         };
         let wallet_req = test::TestRequest::put()
@@ -104,7 +113,7 @@ mod tests {
 
         let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
         let wallet_params = crate::wallet::WalletParams {
-            mnemonic: keys.mnemonic,
+            mnemonic: Some(keys.mnemonic),
             pubkey: keys.xpub,
         };
         let wallet_req = test::TestRequest::put()
@@ -127,4 +136,54 @@ mod tests {
         println!("{:?}", resp);
         assert!(resp.status().is_success());
     }
+
+    #[actix_web::test]
+    async fn test_put_unreachable() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(put)
+                .service(crate::wallet::put),
+        )
+        .await;
+
+        let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+        let wallet_params = crate::wallet::WalletParams {
+            mnemonic: Some(keys.mnemonic),
+            pubkey: keys.xpub,
+        };
+        let wallet_req = test::TestRequest::put()
+            .uri("/wallet")
+            .set_json(wallet_params)
+            .to_request();
+        let wallet_resp = test::call_service(&app, wallet_req).await;
+        println!("{:?}", wallet_resp);
+        assert!(wallet_resp.status().is_success());
+
+        let params = GoOnlineParams {
+            skip_consistency_check: true,
+            electrum_url: "127.0.0.1:1".to_string(),
+        };
+        let req = test::TestRequest::put()
+            .uri("/wallet/go_online")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(!resp.status().is_success());
+
+        // The worker is still serving requests.
+        let params = GoOnlineParams {
+            skip_consistency_check: true,
+            electrum_url: "127.0.0.1:50001".to_string(),
+        };
+        let req = test::TestRequest::put()
+            .uri("/wallet/go_online")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+    }
 }
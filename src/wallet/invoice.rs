@@ -1,4 +1,5 @@
-use actix_web::{put, web, HttpResponse, Responder};
+use crate::error::ShiroError;
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
 use rgb_lib::wallet::{Invoice, InvoiceData};
 use serde::Deserialize;
 use serde::Serialize;
@@ -20,7 +21,7 @@ pub async fn put(params: web::Json<RgbInvoice>) -> impl Responder {
             expiration_timestamp: invoice.invoice_data().expiration_timestamp,
             transport_endpoints: invoice.invoice_data().transport_endpoints,
         }),
-        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+        Err(e) => ShiroError::from(e).error_response(),
     }
 }
 
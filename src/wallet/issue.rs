@@ -1 +1,49 @@
+use crate::error::ShiroError;
+
 pub mod rgb20;
+pub mod rgb25;
+
+const MAX_NAME_LEN: usize = 40;
+const MAX_PRECISION: u8 = 18;
+
+fn invalid(field: &str, message: String) -> ShiroError {
+    ShiroError::InvalidField(field.to_string(), message)
+}
+
+fn check_name(name: &str) -> Result<(), ShiroError> {
+    if name.trim().is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(invalid(
+            "name",
+            format!("should have 1 to {} characters", MAX_NAME_LEN),
+        ));
+    }
+    Ok(())
+}
+
+fn check_precision(precision: u8) -> Result<(), ShiroError> {
+    if precision > MAX_PRECISION {
+        return Err(invalid(
+            "precision",
+            format!("should be at most {}", MAX_PRECISION),
+        ));
+    }
+    Ok(())
+}
+
+/// Every amount must be a positive integer, the failing one is reported as `amounts[i]`.
+fn parse_amounts(amounts: &[String]) -> Result<Vec<u64>, ShiroError> {
+    if amounts.is_empty() {
+        return Err(invalid("amounts", "should not be empty".to_string()));
+    }
+    amounts
+        .iter()
+        .enumerate()
+        .map(|(i, amount)| match amount.parse::<u64>() {
+            Ok(amount) if amount > 0 => Ok(amount),
+            _ => Err(invalid(
+                &format!("amounts[{}]", i),
+                format!("invalid amount: {:?}", amount),
+            )),
+        })
+        .collect()
+}
@@ -1,22 +1,75 @@
-use crate::{wallet::Balance, ShiroWallet};
-use actix_web::{put, web, HttpResponse, Responder};
+use crate::{
+    error::ShiroError,
+    wallet::{
+        blocking,
+        issue::{check_name, check_precision, parse_amounts},
+        Balance,
+    },
+    ShiroWallet,
+};
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
 
+const MAX_TICKER_LEN: usize = 8;
+
 #[derive(Serialize, Deserialize)]
 pub struct Rgb20Params {
     pub ticker: String,
     pub name: String,
+    /// Also accepted as `precision`
+    #[serde(alias = "precision")]
     pub presision: u8,
     pub amounts: Vec<String>,
 }
 
+pub struct Rgb20ParamsForLib {
+    ticker: String,
+    name: String,
+    precision: u8,
+    amounts: Vec<u64>,
+}
+
+impl TryFrom<Rgb20Params> for Rgb20ParamsForLib {
+    type Error = ShiroError;
+
+    fn try_from(x: Rgb20Params) -> Result<Rgb20ParamsForLib, ShiroError> {
+        if x.ticker.is_empty()
+            || x.ticker.len() > MAX_TICKER_LEN
+            || !x.ticker.starts_with(|c: char| c.is_ascii_uppercase())
+            || !x
+                .ticker
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            return Err(ShiroError::InvalidField(
+                "ticker".to_string(),
+                format!(
+                    "should be 1 to {} uppercase letters or digits, starting with a letter",
+                    MAX_TICKER_LEN
+                ),
+            ));
+        }
+        check_name(&x.name)?;
+        check_precision(x.presision)?;
+        let amounts = parse_amounts(&x.amounts)?;
+        Ok(Rgb20ParamsForLib {
+            ticker: x.ticker,
+            name: x.name,
+            precision: x.presision,
+            amounts,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Rgb20Result {
     pub asset_id: String,
     pub ticker: String,
     pub name: String,
+    pub precision: u8,
+    /// Same as `precision`, kept for the clients reading the former key
     pub presision: u8,
     pub balance: Balance,
 }
@@ -28,42 +81,38 @@ pub async fn put(
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
         if data.lock().unwrap().online.is_some() {
-            match actix_web::rt::task::spawn_blocking(move || {
+            let params = match Rgb20ParamsForLib::try_from(params.into_inner()) {
+                Ok(params) => params,
+                Err(e) => return e.error_response(),
+            };
+            match blocking(move || {
                 let mut shiro_wallet = data.lock().unwrap();
-                let online = shiro_wallet.get_online().unwrap();
-                shiro_wallet.wallet.as_mut().unwrap().issue_asset_rgb20(
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                Ok(wallet.issue_asset_rgb20(
                     online,
-                    params.ticker.clone(),
-                    params.name.clone(),
-                    params.presision,
-                    params
-                        .amounts
-                        .clone()
-                        .into_iter()
-                        .flat_map(|str| str.parse::<u64>())
-                        .collect(),
-                )
+                    params.ticker,
+                    params.name,
+                    params.precision,
+                    params.amounts,
+                )?)
             })
             .await
-            .unwrap()
             {
                 Ok(asset) => HttpResponse::Ok().json(Rgb20Result {
                     asset_id: asset.asset_id,
                     ticker: asset.ticker,
                     name: asset.name,
+                    precision: asset.precision,
                     presision: asset.precision,
                     balance: asset.balance.into(),
                 }),
-                Err(e) => {
-                    println!("{:#?}", e);
-                    HttpResponse::BadRequest().body(e.to_string())
-                }
+                Err(e) => e.error_response(),
             }
         } else {
-            HttpResponse::BadRequest().body("wallet should be online")
+            ShiroError::WalletNotOnline.error_response()
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
@@ -94,7 +143,7 @@ mod tests {
         {
             let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
             let params = crate::wallet::WalletParams {
-                mnemonic: keys.mnemonic,
+                mnemonic: Some(keys.mnemonic),
                 pubkey: keys.xpub,
             };
             let req = test::TestRequest::put()
@@ -164,4 +213,59 @@ mod tests {
             assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
         }
     }
+
+    #[test]
+    fn test_params() {
+        let params = |ticker: &str, name: &str, presision: u8, amounts: &[&str]| Rgb20Params {
+            ticker: ticker.to_string(),
+            name: name.to_string(),
+            presision,
+            amounts: amounts.iter().map(|x| x.to_string()).collect(),
+        };
+        let field = |x: Rgb20Params| match Rgb20ParamsForLib::try_from(x) {
+            Err(ShiroError::InvalidField(field, _)) => field,
+            _ => "".to_string(),
+        };
+        let valid =
+            Rgb20ParamsForLib::try_from(params("FAKEMONA", "Fake Monacoin", 8, &["100", "5"]));
+        assert_eq!(valid.unwrap().amounts, vec![100, 5]);
+        assert_eq!(
+            field(params("FAKEMONA", "Fake Monacoin", 8, &["100", "abc"])),
+            "amounts[1]"
+        );
+        assert_eq!(
+            field(params("FAKEMONA", "Fake Monacoin", 8, &["0"])),
+            "amounts[0]"
+        );
+        assert_eq!(
+            field(params("FAKEMONA", "Fake Monacoin", 8, &[])),
+            "amounts"
+        );
+        assert_eq!(
+            field(params("fakemona", "Fake Monacoin", 8, &["100"])),
+            "ticker"
+        );
+        assert_eq!(
+            field(params("FAKEMONACOIN", "Fake Monacoin", 8, &["100"])),
+            "ticker"
+        );
+        assert_eq!(
+            field(params("1MONA", "Fake Monacoin", 8, &["100"])),
+            "ticker"
+        );
+        assert_eq!(field(params("FAKEMONA", "", 8, &["100"])), "name");
+        assert_eq!(
+            field(params("FAKEMONA", "Fake Monacoin", 19, &["100"])),
+            "precision"
+        );
+
+        let params: Rgb20Params = serde_json::from_value(serde_json::json!({
+            "ticker": "FAKEMONA",
+            "name": "Fake Monacoin",
+            "precision": 8,
+            "amounts": ["100"],
+        }))
+        .unwrap();
+        assert_eq!(params.presision, 8);
+    }
 }
@@ -0,0 +1,231 @@
+use crate::{
+    error::ShiroError,
+    wallet::{
+        assets::AssetRgb25,
+        blocking,
+        issue::{check_name, check_precision, parse_amounts},
+    },
+    ShiroWallet,
+};
+use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
+use std::sync::Mutex;
+
+/// Multipart form, `amounts` is repeated once per allocation and `file` is the optional media.
+#[derive(MultipartForm)]
+pub struct Rgb25Form {
+    name: Text<String>,
+    description: Option<Text<String>>,
+    precision: Text<u8>,
+    amounts: Vec<Text<String>>,
+    file: Option<TempFile>,
+}
+
+pub struct Rgb25ParamsForLib {
+    name: String,
+    description: Option<String>,
+    precision: u8,
+    amounts: Vec<u64>,
+    /// Kept until the issuance is done, rgb-lib copies it into the wallet directory
+    file: Option<TempFile>,
+}
+
+impl TryFrom<Rgb25Form> for Rgb25ParamsForLib {
+    type Error = ShiroError;
+
+    fn try_from(x: Rgb25Form) -> Result<Rgb25ParamsForLib, ShiroError> {
+        check_name(&x.name)?;
+        check_precision(*x.precision)?;
+        let amounts = parse_amounts(
+            &x.amounts
+                .into_iter()
+                .map(|amount| amount.into_inner())
+                .collect::<Vec<String>>(),
+        )?;
+        Ok(Rgb25ParamsForLib {
+            name: x.name.into_inner(),
+            description: x.description.map(|description| description.into_inner()),
+            precision: x.precision.into_inner(),
+            amounts,
+            file: x.file.filter(|file| file.size > 0),
+        })
+    }
+}
+
+#[put("/wallet/issue/rgb25")]
+pub async fn put(
+    form: MultipartForm<Rgb25Form>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            let params = match Rgb25ParamsForLib::try_from(form.into_inner()) {
+                Ok(params) => params,
+                Err(e) => return e.error_response(),
+            };
+            match blocking(move || {
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                let file_path = params
+                    .file
+                    .as_ref()
+                    .map(|file| file.file.path().to_string_lossy().to_string());
+                Ok(wallet.issue_asset_rgb25(
+                    online,
+                    params.name,
+                    params.description,
+                    params.precision,
+                    params.amounts,
+                    file_path,
+                )?)
+            })
+            .await
+            {
+                Ok(asset) => HttpResponse::Ok().json(AssetRgb25::from(asset)),
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    use crate::wallet::{
+        address::AddressResult, go_online::GoOnlineParams, tests::fund_wallet, utxos::UtxosParams,
+    };
+    use actix_web::{http, http::header, test, web, App};
+    use rgb_lib::generate_keys;
+
+    pub(crate) const BOUNDARY: &str = "shiro-boundary";
+
+    pub(crate) fn multipart_body(fields: &[(&str, &str)], file: Option<&[u8]>) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (name, value) in fields {
+            body.extend_from_slice(
+                format!(
+                    "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                    BOUNDARY, name, value
+                )
+                .as_bytes(),
+            );
+        }
+        if let Some(file) = file {
+            body.extend_from_slice(
+                format!(
+                    "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"media.txt\"\r\nContent-Type: text/plain\r\n\r\n",
+                    BOUNDARY
+                )
+                .as_bytes(),
+            );
+            body.extend_from_slice(file);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+        body
+    }
+
+    pub fn issue_request(body: Vec<u8>) -> test::TestRequest {
+        test::TestRequest::put()
+            .uri("/wallet/issue/rgb25")
+            .insert_header((
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            ))
+            .set_payload(body)
+    }
+
+    #[actix_web::test]
+    async fn test_put() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(put)
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::utxos::put)
+                .service(crate::wallet::go_online::put),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address.clone());
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = UtxosParams::new(false, Some(1), None, 1.0);
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let body = multipart_body(
+                &[
+                    ("name", "Fake Collectible"),
+                    ("description", "A fake collectible"),
+                    ("precision", "0"),
+                    ("amounts", "10"),
+                ],
+                Some(b"fake media"),
+            );
+            let resp = test::call_service(&app, issue_request(body).to_request()).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let asset: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(asset["name"], "Fake Collectible");
+            assert_eq!(asset["data_paths"].as_array().unwrap().len(), 1);
+        }
+        {
+            let body = multipart_body(
+                &[
+                    ("name", "Fake Collectible"),
+                    ("precision", "0"),
+                    ("amounts", "10"),
+                    ("amounts", "abc"),
+                ],
+                None,
+            );
+            let resp = test::call_service(&app, issue_request(body).to_request()).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::error::ShiroError;
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const KEYSTORE_FILE: &str = "mnemonic.json";
+
+/// Mnemonic encrypted with a key derived from the user passphrase (Argon2id, XChaCha20-Poly1305).
+#[derive(Serialize, Deserialize)]
+pub struct Keystore {
+    pub pubkey: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+impl Keystore {
+    fn path(data_dir: &str) -> PathBuf {
+        Path::new(data_dir).join(KEYSTORE_FILE)
+    }
+
+    pub fn exists(data_dir: &str) -> bool {
+        Keystore::path(data_dir).exists()
+    }
+
+    pub fn encrypt(pubkey: String, mnemonic: &str, passphrase: &str) -> Result<Keystore, String> {
+        let mut rng = rand::thread_rng();
+        let mut salt = [0u8; 16];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; 24];
+        rng.fill_bytes(&mut nonce);
+        let key = derive_key(passphrase, &salt)?;
+        let ciphertext = XChaCha20Poly1305::new(&key.into())
+            .encrypt(XNonce::from_slice(&nonce), mnemonic.as_bytes())
+            .map_err(|e| e.to_string())?;
+        Ok(Keystore {
+            pubkey,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> Result<String, ShiroError> {
+        let internal = |e: String| ShiroError::Internal(e);
+        let salt = hex::decode(&self.salt).map_err(|e| internal(e.to_string()))?;
+        let nonce = hex::decode(&self.nonce).map_err(|e| internal(e.to_string()))?;
+        let ciphertext = hex::decode(&self.ciphertext).map_err(|e| internal(e.to_string()))?;
+        if nonce.len() != 24 {
+            return Err(internal("corrupted keystore".to_string()));
+        }
+        let key = derive_key(passphrase, &salt).map_err(internal)?;
+        let plaintext = XChaCha20Poly1305::new(&key.into())
+            .decrypt(XNonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| ShiroError::WrongPassphrase)?;
+        String::from_utf8(plaintext).map_err(|e| internal(e.to_string()))
+    }
+
+    pub fn load(data_dir: &str) -> std::io::Result<Option<Keystore>> {
+        let path = Keystore::path(data_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&fs::read(path)?)?))
+    }
+
+    pub fn save(&self, data_dir: &str) -> std::io::Result<()> {
+        let path = Keystore::path(data_dir);
+        let tmp_path = path.with_extension("json.tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.sync_all()?;
+        fs::rename(tmp_path, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let mnemonic = "save call film frog usual market noodle hope stomach chat word worry";
+        let keystore = Keystore::encrypt("tpub".to_string(), mnemonic, "passphrase").unwrap();
+        assert!(!keystore.ciphertext.contains("save"));
+        assert_eq!(keystore.decrypt("passphrase").unwrap(), mnemonic);
+        assert!(matches!(
+            keystore.decrypt("wrong"),
+            Err(ShiroError::WrongPassphrase)
+        ));
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{
+    error::ShiroError,
+    wallet::{events::WalletEvent, keystore::Keystore},
+    ShiroWallet,
+};
+use actix_web::{post, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize)]
+pub struct LockResult {}
+
+/// Locks the wallet when it hasn't been used for `timeout`.
+/// Wallets without a passphrase are never locked since they couldn't be unlocked.
+pub fn lock_if_idle(data: &Mutex<ShiroWallet>, timeout: Duration) -> bool {
+    // A busy wallet isn't idle.
+    let mut shiro_wallet = match data.try_lock() {
+        Ok(shiro_wallet) => shiro_wallet,
+        Err(_) => return false,
+    };
+    if shiro_wallet.wallet.is_none()
+        || shiro_wallet.last_used.elapsed() < timeout
+        || !Keystore::exists(&shiro_wallet.get_data_dir())
+    {
+        return false;
+    }
+    shiro_wallet.events.notify(WalletEvent::Locked);
+    shiro_wallet.lock();
+    true
+}
+
+#[post("/wallet/lock")]
+pub async fn post(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
+    let mut shiro_wallet = data.lock().unwrap();
+    if !Keystore::exists(&shiro_wallet.get_data_dir()) {
+        return ShiroError::PassphraseNotSet.error_response();
+    }
+    if shiro_wallet.wallet.is_some() {
+        shiro_wallet.events.notify(WalletEvent::Locked);
+    }
+    shiro_wallet.lock();
+    HttpResponse::Ok().json(LockResult {})
+}
@@ -0,0 +1,101 @@
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, data::mnemonic::RevealLimiter, keystore::Keystore},
+    ShiroWallet,
+};
+use actix_web::{put, web, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+#[derive(Serialize, Deserialize)]
+pub struct PassphraseParams {
+    pub passphrase: String,
+    /// Required to change the passphrase of a wallet which already has one
+    #[serde(default)]
+    pub old_passphrase: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PassphraseResult {}
+
+/// Stores the mnemonic encrypted with `passphrase`, so the wallet can be locked and unlocked.
+/// An existing passphrase is only replaced when `old_passphrase` matches it, checks which share
+/// the limit of `POST /wallet/unlock`.
+#[put("/wallet/passphrase")]
+pub async fn put(
+    req: HttpRequest,
+    params: web::Json<PassphraseParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+    limiter: web::Data<RevealLimiter>,
+) -> impl Responder {
+    if params.passphrase.len() < MIN_PASSPHRASE_LEN {
+        return ShiroError::InvalidParams(format!(
+            "passphrase should have at least {} characters",
+            MIN_PASSPHRASE_LEN
+        ))
+        .error_response();
+    }
+    let wallet_data = match &data.lock().unwrap().wallet {
+        Some(wallet) => wallet.get_wallet_data(),
+        None => return ShiroError::WalletNotCreated.error_response(),
+    };
+    let mnemonic = match wallet_data.mnemonic {
+        Some(mnemonic) => mnemonic,
+        None => return ShiroError::WatchOnly.error_response(),
+    };
+    let peer = req.peer_addr().map(|x| x.ip().to_string());
+    match blocking(move || {
+        let data_dir = data.lock().unwrap().get_data_dir();
+        let old_keystore =
+            Keystore::load(&data_dir).map_err(|e| ShiroError::Internal(e.to_string()))?;
+        let changing = old_keystore.is_some();
+        if let Some(old_keystore) = old_keystore {
+            let old_passphrase = match &params.old_passphrase {
+                Some(old_passphrase) => old_passphrase,
+                None => {
+                    return Err(ShiroError::InvalidField(
+                        "old_passphrase".to_string(),
+                        "required to change the passphrase".to_string(),
+                    ))
+                }
+            };
+            if !limiter.try_attempt(&data_dir, peer.as_deref()) {
+                crate::audit::log(&data_dir, "change_passphrase", peer, "rate-limited");
+                return Err(ShiroError::TooManyRequests);
+            }
+            if let Err(e) = old_keystore.decrypt(old_passphrase) {
+                let outcome = match e {
+                    ShiroError::WrongPassphrase => "denied",
+                    _ => "failed",
+                };
+                crate::audit::log(&data_dir, "change_passphrase", peer, outcome);
+                return Err(e);
+            }
+        }
+        let result = Keystore::encrypt(wallet_data.pubkey, &mnemonic, &params.passphrase)
+            .map_err(ShiroError::Internal)
+            .and_then(|keystore| {
+                let shiro_wallet = data.lock().unwrap();
+                keystore
+                    .save(&shiro_wallet.get_data_dir())
+                    .map_err(|e| ShiroError::Internal(e.to_string()))?;
+                // The wallet can now be recorded by `--reopen-wallets`, to be unlocked after a restart.
+                shiro_wallet
+                    .save_record()
+                    .map_err(|e| ShiroError::Internal(e.to_string()))
+            });
+        if changing {
+            let outcome = if result.is_ok() { "changed" } else { "failed" };
+            crate::audit::log(&data_dir, "change_passphrase", peer, outcome);
+        }
+        result
+    })
+    .await
+    {
+        Ok(_) => HttpResponse::Ok().json(PassphraseResult {}),
+        Err(e) => e.error_response(),
+    }
+}
@@ -0,0 +1,139 @@
+use bitcoin::psbt::PartiallySignedTransaction;
+use bitcoin::{Address, Network};
+use rgb_lib::BitcoinNetwork;
+use serde::Deserialize;
+use serde::Serialize;
+use std::str::FromStr;
+
+pub mod sign;
+
+/// Witness of a P2WPKH input, missing from the unsigned transaction
+const P2WPKH_WITNESS_VSIZE: usize = 27;
+
+#[derive(Serialize, Deserialize)]
+pub struct PsbtInput {
+    pub outpoint: String,
+    /// Unknown when the PSBT doesn't carry the spent output
+    amount: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PsbtOutput {
+    address: Option<String>,
+    amount: String,
+    /// Whether it pays back to the wallet (change or created UTXOs), as told by its key origin
+    pub is_mine: bool,
+}
+
+/// Human readable content of a PSBT, to be reviewed before signing
+#[derive(Serialize, Deserialize)]
+pub struct PsbtSummary {
+    pub txid: String,
+    pub inputs: Vec<PsbtInput>,
+    pub outputs: Vec<PsbtOutput>,
+    pub fee: Option<String>,
+    /// Estimated size once signed, in vbytes
+    pub vsize: usize,
+}
+
+impl PsbtSummary {
+    pub fn new(psbt: &str, bitcoin_network: BitcoinNetwork) -> Result<PsbtSummary, String> {
+        let network = match bitcoin_network {
+            BitcoinNetwork::Mainnet => Network::Bitcoin,
+            BitcoinNetwork::Testnet => Network::Testnet,
+            BitcoinNetwork::Regtest => Network::Regtest,
+            BitcoinNetwork::Signet => Network::Signet,
+        };
+        let psbt = PartiallySignedTransaction::from_str(psbt).map_err(|e| e.to_string())?;
+        let tx = &psbt.unsigned_tx;
+        let input_amounts = tx
+            .input
+            .iter()
+            .zip(psbt.inputs.iter())
+            .map(
+                |(txin, input)| match (&input.witness_utxo, &input.non_witness_utxo) {
+                    (Some(txout), _) => Some(txout.value),
+                    (None, Some(prev_tx)) => prev_tx
+                        .output
+                        .get(txin.previous_output.vout as usize)
+                        .map(|txout| txout.value),
+                    (None, None) => None,
+                },
+            )
+            .collect::<Vec<Option<u64>>>();
+        let fee = input_amounts
+            .iter()
+            .copied()
+            .sum::<Option<u64>>()
+            .and_then(|total_in| {
+                total_in.checked_sub(tx.output.iter().map(|txout| txout.value).sum::<u64>())
+            });
+        Ok(PsbtSummary {
+            txid: tx.txid().to_string(),
+            inputs: tx
+                .input
+                .iter()
+                .zip(input_amounts)
+                .map(|(txin, amount)| PsbtInput {
+                    outpoint: txin.previous_output.to_string(),
+                    amount: amount.map(|x| x.to_string()),
+                })
+                .collect::<Vec<PsbtInput>>(),
+            outputs: tx
+                .output
+                .iter()
+                .zip(psbt.outputs.iter())
+                .map(|(txout, output)| PsbtOutput {
+                    address: Address::from_script(&txout.script_pubkey, network)
+                        .map(|x| x.to_string()),
+                    amount: txout.value.to_string(),
+                    is_mine: !output.bip32_derivation.is_empty()
+                        || !output.tap_key_origins.is_empty(),
+                })
+                .collect::<Vec<PsbtOutput>>(),
+            fee: fee.map(|x| x.to_string()),
+            vsize: tx.vsize() + tx.input.len() * P2WPKH_WITNESS_VSIZE,
+        })
+    }
+
+    /// Bitcoin leaving the wallet: the fee and the outputs paying someone else.
+    pub fn spent(&self) -> Option<u64> {
+        let fee = self.fee.as_ref()?.parse::<u64>().ok()?;
+        self.outputs
+            .iter()
+            .filter(|output| !output.is_mine)
+            .try_fold(fee, |total, output| {
+                Some(total + output.amount.parse::<u64>().ok()?)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_psbt_summary() {
+        let psbt = "cHNidP8BAFICAAAAAQABAgMEBQYHCAkKCwwNDg8QERITFBUWFxgZGhscHR4fAAAAAAD/////AbiCAQAAAAAAFgAUEREREREREREREREREREREREREREAAAAAAAEBH6CGAQAAAAAAFgAUIiIiIiIiIiIiIiIiIiIiIiIiIiIAAA==";
+        let summary = PsbtSummary::new(psbt, BitcoinNetwork::Regtest).unwrap();
+        assert_eq!(summary.inputs.len(), 1);
+        assert!(summary.inputs[0].outpoint.ends_with(":0"));
+        assert_eq!(summary.inputs[0].amount, Some("100000".to_string()));
+        assert_eq!(summary.outputs.len(), 1);
+        assert_eq!(summary.outputs[0].amount, "99000");
+        assert!(summary.outputs[0]
+            .address
+            .as_ref()
+            .unwrap()
+            .starts_with("bcrt1q"));
+        assert!(!summary.outputs[0].is_mine);
+        assert_eq!(summary.fee, Some("1000".to_string()));
+        assert_eq!(summary.vsize, 109);
+        assert_eq!(summary.spent(), Some(100000));
+    }
+
+    #[test]
+    fn test_psbt_summary_invalid() {
+        assert!(PsbtSummary::new("helloPSBT", BitcoinNetwork::Regtest).is_err());
+    }
+}
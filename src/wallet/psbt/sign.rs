@@ -0,0 +1,40 @@
+use crate::{error::ShiroError, wallet::blocking, ShiroWallet};
+use actix_web::{post, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize)]
+pub struct SignParams {
+    pub unsigned_psbt: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignResult {
+    pub signed_psbt: String,
+}
+
+#[post("/wallet/psbt/sign")]
+pub async fn post(
+    params: web::Json<SignParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().is_watch_only() {
+            return ShiroError::WatchOnly.error_response();
+        }
+        match blocking(move || {
+            let mut shiro_wallet = data.lock().unwrap();
+            Ok(shiro_wallet
+                .wallet_mut()?
+                .sign_psbt(params.unsigned_psbt.clone())?)
+        })
+        .await
+        {
+            Ok(signed_psbt) => HttpResponse::Ok().json(SignResult { signed_psbt }),
+            Err(e) => e.error_response(),
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{wallet::keystore::Keystore, ShiroWallet};
+use rgb_lib::wallet::{Wallet, WalletData};
+use rgb_lib::BitcoinNetwork;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const RECORD_FILE: &str = "shiro-wallet.json";
+
+/// What is needed to reopen a wallet after a restart, stored in the wallet's `data_dir`.
+/// The mnemonic is never part of it, hot wallets are only recorded once they have a keystore.
+#[derive(Serialize, Deserialize)]
+pub struct WalletRecord {
+    /// Fingerprint of the wallet xpub (name of the rgb-lib wallet directory)
+    pub fingerprint: String,
+    pub bitcoin_network: BitcoinNetwork,
+    pub pubkey: String,
+    /// Electrum server the wallet went online with last time
+    pub electrum_url: Option<String>,
+    /// The mnemonic is in the keystore, the wallet waits to be unlocked
+    #[serde(default)]
+    pub encrypted: bool,
+    #[serde(default)]
+    pub watch_only: bool,
+}
+
+impl WalletRecord {
+    fn path(data_dir: &str) -> PathBuf {
+        Path::new(data_dir).join(RECORD_FILE)
+    }
+
+    pub fn load(data_dir: &str) -> std::io::Result<Option<WalletRecord>> {
+        let path = WalletRecord::path(data_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let record = serde_json::from_slice(&fs::read(path)?)?;
+        Ok(Some(record))
+    }
+
+    pub fn save(&self, data_dir: &str) -> std::io::Result<()> {
+        let path = WalletRecord::path(data_dir);
+        let tmp_path = path.with_extension("json.tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.sync_all()?;
+        fs::rename(tmp_path, path)
+    }
+
+    pub fn remove(data_dir: &str) -> std::io::Result<()> {
+        let path = WalletRecord::path(data_dir);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl ShiroWallet {
+    /// Stores or removes the record of this wallet when `reopen_wallets` is set.
+    pub fn save_record(&self) -> std::io::Result<()> {
+        if !self.reopen_wallets {
+            return Ok(());
+        }
+        let data_dir = self.get_data_dir();
+        let wallet = match &self.wallet {
+            Some(wallet) => wallet,
+            None => return WalletRecord::remove(&data_dir),
+        };
+        let wdata = wallet.get_wallet_data();
+        let encrypted = Keystore::exists(&data_dir);
+        let watch_only = wdata.mnemonic.is_none();
+        let fingerprint = wallet
+            .get_wallet_dir()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !encrypted && !watch_only {
+            println!(
+                "wallet {} won't be reopened until a passphrase is set",
+                fingerprint
+            );
+            return WalletRecord::remove(&data_dir);
+        }
+        WalletRecord {
+            fingerprint,
+            bitcoin_network: wdata.bitcoin_network,
+            pubkey: wdata.pubkey,
+            electrum_url: self.online.as_ref().map(|x| x.electrum_url.clone()),
+            encrypted,
+            watch_only,
+        }
+        .save(&data_dir)
+    }
+
+    /// Restores the wallet, and its online state, from the record in its `data_dir`.
+    pub fn reopen(&mut self, skip_consistency_check: bool) -> Result<bool, String> {
+        let data_dir = self.get_data_dir();
+        let record = match WalletRecord::load(&data_dir).map_err(|e| e.to_string())? {
+            Some(record) => record,
+            None => return Ok(false),
+        };
+        if record.encrypted {
+            println!("wallet {} is locked", record.fingerprint);
+            return Ok(false);
+        }
+        if !record.watch_only {
+            // Written by an older version, along with the mnemonic in plaintext.
+            WalletRecord::remove(&data_dir).map_err(|e| e.to_string())?;
+            return Err(format!(
+                "wallet {} has no passphrase, its record has been removed",
+                record.fingerprint
+            ));
+        }
+        let base_data = shiro_backend::opts::get_wallet_data();
+        if record.bitcoin_network != base_data.bitcoin_network {
+            return Err(format!(
+                "wallet {} was created for another bitcoin network",
+                record.fingerprint
+            ));
+        }
+        let mut wallet = Wallet::new(WalletData {
+            data_dir,
+            bitcoin_network: base_data.bitcoin_network,
+            database_type: base_data.database_type,
+            pubkey: record.pubkey,
+            mnemonic: None,
+        })
+        .map_err(|e| e.to_string())?;
+        if let Some(electrum_url) = record.electrum_url {
+            match wallet.go_online(skip_consistency_check, electrum_url) {
+                Ok(online) => self.online = Some(online),
+                Err(e) => println!(
+                    "wallet {} has been reopened offline: {}",
+                    record.fingerprint, e
+                ),
+            }
+        }
+        self.wallet = Some(wallet);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let data_dir = std::env::temp_dir().join("shiro-wallet-record-test");
+        fs::create_dir_all(&data_dir).unwrap();
+        let data_dir = data_dir.to_string_lossy().to_string();
+
+        let record = WalletRecord {
+            fingerprint: "60ec7707".to_string(),
+            bitcoin_network: BitcoinNetwork::Regtest,
+            pubkey: "tpubD6NzVbkrYhZ4YT9CY6kBTU8xYWq2GQPq4NYzaJer1CRrffVLwzYt5Rs3WhjZJGKaNaiN42JfgtnyGwHXc5n5oPbAUSbxwuwDqZci5kdAZHb".to_string(),
+            electrum_url: Some("127.0.0.1:50001".to_string()),
+            encrypted: false,
+            watch_only: true,
+        };
+        record.save(&data_dir).unwrap();
+        let loaded = WalletRecord::load(&data_dir).unwrap().unwrap();
+        assert_eq!(loaded.fingerprint, record.fingerprint);
+        assert_eq!(loaded.bitcoin_network, record.bitcoin_network);
+        assert_eq!(loaded.pubkey, record.pubkey);
+        assert_eq!(loaded.electrum_url, record.electrum_url);
+        assert!(loaded.watch_only);
+
+        WalletRecord::remove(&data_dir).unwrap();
+        assert!(WalletRecord::load(&data_dir).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_save_record_requires_passphrase() {
+        let data_dir = std::env::temp_dir().join(format!(
+            "shiro-wallet-record-test-{}",
+            hex::encode(rand::random::<[u8; 4]>())
+        ));
+        fs::create_dir_all(&data_dir).unwrap();
+        let data_dir = data_dir.to_string_lossy().to_string();
+        let keys = rgb_lib::generate_keys(BitcoinNetwork::Regtest);
+        let base_data = shiro_backend::opts::get_wallet_data();
+        let wallet = Wallet::new(WalletData {
+            data_dir: data_dir.clone(),
+            bitcoin_network: base_data.bitcoin_network,
+            database_type: base_data.database_type,
+            pubkey: keys.xpub.clone(),
+            mnemonic: Some(keys.mnemonic.clone()),
+        })
+        .unwrap();
+        let mut shiro_wallet = ShiroWallet::with_data_dir(data_dir.clone());
+        shiro_wallet.reopen_wallets = true;
+        shiro_wallet.wallet = Some(wallet);
+
+        // Without a keystore, nothing would protect the mnemonic.
+        shiro_wallet.save_record().unwrap();
+        assert!(WalletRecord::load(&data_dir).unwrap().is_none());
+
+        Keystore::encrypt(keys.xpub, &keys.mnemonic, "correct horse battery")
+            .unwrap()
+            .save(&data_dir)
+            .unwrap();
+        shiro_wallet.save_record().unwrap();
+        let record = WalletRecord::load(&data_dir).unwrap().unwrap();
+        assert!(record.encrypted);
+        assert!(!record.watch_only);
+        let content = fs::read_to_string(WalletRecord::path(&data_dir)).unwrap();
+        assert!(!content.contains(&keys.mnemonic));
+
+        fs::remove_dir_all(data_dir).unwrap();
+    }
+}
@@ -1,9 +1,12 @@
-use crate::ShiroWallet;
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::{error::ShiroError, wallet::blocking, ShiroWallet};
+use actix_web::{post, web, HttpResponse, Responder, ResponseError};
 use rgb_lib::wallet::RefreshTransferStatus;
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub mod status;
 
 #[derive(Serialize, Deserialize)]
 pub struct RefreshParams {
@@ -17,16 +20,23 @@ struct RefreshFilter {
     incoming: bool,
 }
 
-impl RefreshFilter {
-    fn conv(&self) -> rgb_lib::wallet::RefreshFilter {
-        rgb_lib::wallet::RefreshFilter {
-            status: match self.status.as_str() {
+impl TryFrom<&RefreshFilter> for rgb_lib::wallet::RefreshFilter {
+    type Error = ShiroError;
+
+    fn try_from(x: &RefreshFilter) -> Result<rgb_lib::wallet::RefreshFilter, ShiroError> {
+        Ok(rgb_lib::wallet::RefreshFilter {
+            status: match x.status.as_str() {
                 "WaitingCounterparty" => RefreshTransferStatus::WaitingCounterparty,
                 "WaitingConfirmations" => RefreshTransferStatus::WaitingConfirmations,
-                &_ => panic!("Unknown status"),
+                status => {
+                    return Err(ShiroError::InvalidParams(format!(
+                        "unknown status: {:?}",
+                        status
+                    )))
+                }
             },
-            incoming: self.incoming,
-        }
+            incoming: x.incoming,
+        })
     }
 }
 
@@ -35,6 +45,92 @@ pub struct RefreshResult {
     result: bool,
 }
 
+/// Outcome of the last refresh of a wallet, see `GET /wallet/refresh/status`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RefreshStatus {
+    /// Unix time the refresh finished at
+    pub timestamp: u64,
+    /// Whether it has been run by `--auto-refresh-seconds`
+    pub automatic: bool,
+    pub transfers_changed: bool,
+    pub error: Option<String>,
+}
+
+impl RefreshStatus {
+    fn new(automatic: bool, result: &Result<bool, ShiroError>) -> RefreshStatus {
+        RefreshStatus {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|x| x.as_secs())
+                .unwrap_or_default(),
+            automatic,
+            transfers_changed: *result.as_ref().unwrap_or(&false),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// Keeps the outcome of a refresh and publishes the changes it made.
+fn record(
+    shiro_wallet: &mut ShiroWallet,
+    automatic: bool,
+    result: Result<bool, rgb_lib::Error>,
+) -> Result<bool, ShiroError> {
+    let result = result.map_err(ShiroError::from);
+    shiro_wallet.last_refresh = Some(RefreshStatus::new(automatic, &result));
+    let online = shiro_wallet.online.clone();
+    if let Some(wallet) = shiro_wallet.wallet.as_mut() {
+        shiro_wallet.events.publish(wallet, online, &result);
+    }
+    result
+}
+
+/// Every pending transfer, split in groups refreshed one after the other.
+fn pending_filters() -> Vec<rgb_lib::wallet::RefreshFilter> {
+    let mut filters = vec![];
+    for incoming in [true, false] {
+        filters.push(rgb_lib::wallet::RefreshFilter {
+            status: RefreshTransferStatus::WaitingCounterparty,
+            incoming,
+        });
+        filters.push(rgb_lib::wallet::RefreshFilter {
+            status: RefreshTransferStatus::WaitingConfirmations,
+            incoming,
+        });
+    }
+    filters
+}
+
+/// Refreshes every pending transfer of an online wallet, `None` when it isn't online.
+/// The wallet is only locked for one group of transfers at a time, so requests are served
+/// in between. Blocks, to be called from `spawn_blocking`.
+pub fn refresh_pending(data: &Mutex<ShiroWallet>) -> Option<RefreshStatus> {
+    let mut result = Ok(false);
+    for filter in pending_filters() {
+        let mut shiro_wallet = data.lock().unwrap();
+        let online = shiro_wallet.get_online()?;
+        match shiro_wallet
+            .wallet
+            .as_mut()?
+            .refresh(online, None, vec![filter])
+        {
+            Ok(transfers_changed) => {
+                result = result.map(|changed| changed || transfers_changed);
+            }
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    let mut shiro_wallet = data.lock().unwrap();
+    if shiro_wallet.wallet.is_none() {
+        return None;
+    }
+    let _ = record(&mut shiro_wallet, true, result);
+    shiro_wallet.last_refresh.clone()
+}
+
 #[post("/wallet/refresh")]
 pub async fn post(
     params: web::Json<RefreshParams>,
@@ -42,30 +138,31 @@ pub async fn post(
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
         if data.lock().unwrap().online.is_some() {
-            match actix_web::rt::task::spawn_blocking(move || {
+            let filter = match params
+                .filter
+                .iter()
+                .map(rgb_lib::wallet::RefreshFilter::try_from)
+                .collect::<Result<Vec<rgb_lib::wallet::RefreshFilter>, ShiroError>>()
+            {
+                Ok(filter) => filter,
+                Err(e) => return e.error_response(),
+            };
+            match blocking(move || {
                 let mut shiro_wallet = data.lock().unwrap();
-                let online = shiro_wallet.get_online().unwrap();
-                shiro_wallet.wallet.as_mut().unwrap().refresh(
-                    online,
-                    params.asset_id.clone(),
-                    params
-                        .filter
-                        .iter()
-                        .map(|x| x.conv())
-                        .collect::<Vec<rgb_lib::wallet::RefreshFilter>>(),
-                )
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                let result = wallet.refresh(online, params.asset_id.clone(), filter);
+                record(&mut shiro_wallet, false, result)
             })
             .await
-            .unwrap()
             {
                 Ok(result) => HttpResponse::Ok().json(RefreshResult { result }),
-                Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+                Err(e) => e.error_response(),
             }
         } else {
-            HttpResponse::BadRequest().body("wallet should be online")
+            ShiroError::WalletNotOnline.error_response()
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
@@ -80,7 +177,7 @@ mod tests {
         tests::{fund_wallet, gen_fake_ticker},
         utxos::UtxosParams,
     };
-    use actix_web::{test, web, App};
+    use actix_web::{http, test, web, App};
     use rgb_lib::generate_keys;
 
     #[actix_web::test]
@@ -101,7 +198,7 @@ mod tests {
         {
             let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
             let params = crate::wallet::WalletParams {
-                mnemonic: keys.mnemonic,
+                mnemonic: Some(keys.mnemonic),
                 pubkey: keys.xpub,
             };
             let req = test::TestRequest::put()
@@ -164,4 +261,63 @@ mod tests {
         let res: RefreshResult = test::call_and_read_body_json(&app, req).await;
         assert!(!res.result);
     }
+
+    #[actix_web::test]
+    async fn test_post_unknown_status() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::go_online::put)
+                .service(post),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let params = RefreshParams {
+            asset_id: None,
+            filter: vec![RefreshFilter {
+                status: "Settled".to_string(),
+                incoming: true,
+            }],
+        };
+        let req = test::TestRequest::post()
+            .uri("/wallet/refresh")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        // The worker is still serving requests.
+        let req = test::TestRequest::get().uri("/wallet/address").to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+    }
 }
@@ -0,0 +1,90 @@
+use crate::{error::ShiroError, wallet::refresh::RefreshStatus, ShiroWallet};
+use actix_web::{get, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize)]
+pub struct RefreshStatusResult {
+    /// `null` until the wallet has been refreshed since the server started
+    last_refresh: Option<RefreshStatus>,
+}
+
+#[get("/wallet/refresh/status")]
+pub async fn get(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
+    let shiro_wallet = data.lock().unwrap();
+    if shiro_wallet.wallet.is_some() {
+        HttpResponse::Ok().json(RefreshStatusResult {
+            last_refresh: shiro_wallet.last_refresh.clone(),
+        })
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::{go_online::GoOnlineParams, refresh::refresh_pending};
+    use actix_web::{test, App};
+    use rgb_lib::generate_keys;
+
+    #[actix_web::test]
+    async fn test_get() {
+        let data = web::Data::new(Mutex::new(ShiroWallet::new()));
+        let app = test::init_service(
+            App::new()
+                .app_data(data.clone())
+                .service(crate::wallet::put)
+                .service(crate::wallet::go_online::put)
+                .service(get),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let req = test::TestRequest::get()
+                .uri("/wallet/refresh/status")
+                .to_request();
+            let result: RefreshStatusResult = test::call_and_read_body_json(&app, req).await;
+            assert!(result.last_refresh.is_none());
+        }
+        // Offline wallets are skipped.
+        assert!(refresh_pending(&data).is_none());
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let status = refresh_pending(&data).unwrap();
+        assert!(status.automatic);
+        assert!(status.error.is_none());
+
+        let req = test::TestRequest::get()
+            .uri("/wallet/refresh/status")
+            .to_request();
+        let result: RefreshStatusResult = test::call_and_read_body_json(&app, req).await;
+        let last_refresh = result.last_refresh.unwrap();
+        assert!(last_refresh.automatic);
+        assert_eq!(last_refresh.timestamp, status.timestamp);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{
+    error::ShiroError,
+    wallet::{backup::check_password, blocking, record::WalletRecord},
+    ShiroWallet,
+};
+use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
+use actix_web::{post, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Multipart form, `file` is the archive downloaded from `POST /wallet/backup`.
+#[derive(MultipartForm)]
+pub struct RestoreForm {
+    password: Text<String>,
+    file: TempFile,
+    /// Fingerprint of the keys the wallet will be opened with, the archive of another wallet is refused
+    fingerprint: Option<Text<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RestoreResult {
+    /// Fingerprint of the restored wallet
+    pub fingerprint: String,
+}
+
+/// The fingerprint of the wallet extracted in `staging_dir`, the name of its only directory.
+fn restored_fingerprint(staging_dir: &Path) -> Result<String, ShiroError> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(staging_dir).map_err(|e| ShiroError::Internal(e.to_string()))? {
+        let entry = entry.map_err(|e| ShiroError::Internal(e.to_string()))?;
+        if entry.path().is_dir() {
+            dirs.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    match dirs.as_slice() {
+        [fingerprint] => Ok(fingerprint.clone()),
+        _ => Err(ShiroError::InvalidField(
+            "file".to_string(),
+            "the archive should hold one wallet directory".to_string(),
+        )),
+    }
+}
+
+fn check_fingerprint(fingerprint: &str, expected: &str) -> Result<(), ShiroError> {
+    if fingerprint != expected {
+        return Err(ShiroError::InvalidField(
+            "file".to_string(),
+            format!(
+                "the archive is a backup of wallet {}, not {}",
+                fingerprint, expected
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Re-creates the wallet directory from a backup, the wallet is then opened with `PUT /wallet`.
+#[post("/wallet/restore")]
+pub async fn post(
+    form: MultipartForm<RestoreForm>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        return ShiroError::WalletAlreadyCreated.error_response();
+    }
+    let form = form.into_inner();
+    if let Err(e) = check_password(&form.password) {
+        return e.error_response();
+    }
+    match blocking(move || {
+        // Held until the directory is in place, so that `PUT /wallet` can't open it meanwhile.
+        let shiro_wallet = data.lock().unwrap();
+        if shiro_wallet.wallet.is_some() {
+            return Err(ShiroError::WalletAlreadyCreated);
+        }
+        let data_dir = shiro_wallet.get_data_dir();
+        let staging_dir = Path::new(&data_dir).join(format!(
+            ".restore-{}",
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+        fs::create_dir_all(&staging_dir).map_err(|e| ShiroError::Internal(e.to_string()))?;
+        let backup_path = form.file.file.path().to_string_lossy().to_string();
+        let result =
+            rgb_lib::restore_backup(&backup_path, &form.password, &staging_dir.to_string_lossy())
+                .map_err(ShiroError::from)
+                .and_then(|_| {
+                    let fingerprint = restored_fingerprint(&staging_dir)?;
+                    if let Some(expected) = &form.fingerprint {
+                        check_fingerprint(&fingerprint, expected)?;
+                    }
+                    let record = WalletRecord::load(&data_dir)
+                        .map_err(|e| ShiroError::Internal(e.to_string()))?;
+                    if let Some(record) = record {
+                        check_fingerprint(&fingerprint, &record.fingerprint)?;
+                    }
+                    let wallet_dir = Path::new(&data_dir).join(&fingerprint);
+                    if wallet_dir.exists() {
+                        return Err(ShiroError::WalletAlreadyCreated);
+                    }
+                    fs::rename(staging_dir.join(&fingerprint), wallet_dir)
+                        .map_err(|e| ShiroError::Internal(e.to_string()))?;
+                    Ok(fingerprint)
+                });
+        let _ = fs::remove_dir_all(&staging_dir);
+        result
+    })
+    .await
+    {
+        Ok(fingerprint) => HttpResponse::Ok().json(RestoreResult { fingerprint }),
+        Err(e) => e.error_response(),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::wallet::issue::rgb25::tests::{multipart_body, BOUNDARY};
+    use actix_web::{http::header, test};
+
+    pub fn restore_request(
+        password: &str,
+        fingerprint: Option<&str>,
+        archive: &[u8],
+    ) -> test::TestRequest {
+        let mut fields = vec![("password", password)];
+        if let Some(fingerprint) = fingerprint {
+            fields.push(("fingerprint", fingerprint));
+        }
+        test::TestRequest::post()
+            .uri("/wallet/restore")
+            .insert_header((
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            ))
+            .set_payload(multipart_body(&fields, Some(archive)))
+    }
+}
@@ -1,15 +1,49 @@
-use crate::ShiroWallet;
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::{
+    error::ShiroError,
+    wallet::{
+        blocking,
+        dry_run::{DryRunResult, Moved},
+        fees::FeeRate,
+        UnsignedPsbtResult,
+    },
+    ShiroWallet,
+};
+use actix_web::{post, web, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::Mutex;
 
+pub mod begin;
+pub mod end;
+
 #[derive(Serialize, Deserialize)]
 pub struct SendParams {
     recipient_map: HashMap<String, Vec<Recipient>>,
     donation: bool,
-    fee_rate: f32,
+    fee_rate: FeeRate,
+    /// Only return what the transaction would be, see [`DryRunResult`]
+    #[serde(default)]
+    dry_run: bool,
+}
+
+impl SendParams {
+    pub fn get_recipient_map(
+        &self,
+    ) -> Result<HashMap<String, Vec<rgb_lib::wallet::Recipient>>, ShiroError> {
+        self.recipient_map
+            .iter()
+            .map(|(asset_id, recipients)| {
+                Ok((
+                    asset_id.clone(),
+                    recipients
+                        .iter()
+                        .map(rgb_lib::wallet::Recipient::try_from)
+                        .collect::<Result<Vec<rgb_lib::wallet::Recipient>, ShiroError>>()?,
+                ))
+            })
+            .collect()
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -19,13 +53,17 @@ struct Recipient {
     transport_endpoints: Vec<String>,
 }
 
-impl Recipient {
-    pub fn conv(&self) -> rgb_lib::wallet::Recipient {
-        rgb_lib::wallet::Recipient {
-            blinded_utxo: self.blinded_utxo.clone(),
-            amount: str::parse::<u64>(&self.amount).unwrap(),
-            transport_endpoints: self.transport_endpoints.clone(),
-        }
+impl TryFrom<&Recipient> for rgb_lib::wallet::Recipient {
+    type Error = ShiroError;
+
+    fn try_from(x: &Recipient) -> Result<rgb_lib::wallet::Recipient, ShiroError> {
+        Ok(rgb_lib::wallet::Recipient {
+            blinded_utxo: x.blinded_utxo.clone(),
+            amount: x.amount.parse::<u64>().map_err(|_| {
+                ShiroError::InvalidParams(format!("invalid amount: {:?}", x.amount))
+            })?,
+            transport_endpoints: x.transport_endpoints.clone(),
+        })
     }
 }
 
@@ -51,62 +89,84 @@ pub async fn post(
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
         if data.lock().unwrap().online.is_some() {
-            match actix_web::rt::task::spawn_blocking(move || {
-                let mut shiro_wallet = data.lock().unwrap();
-                let online = shiro_wallet.get_online().unwrap();
-                let recipient_map = params
-                    .recipient_map
-                    .iter()
-                    .map(|(psbt, recipients)| {
-                        (
-                            psbt.clone(),
-                            recipients
-                                .iter()
-                                .map(|recipient| recipient.conv())
-                                .collect::<Vec<rgb_lib::wallet::Recipient>>(),
-                        )
+            let recipient_map = match params.get_recipient_map() {
+                Ok(recipient_map) => recipient_map,
+                Err(e) => return e.error_response(),
+            };
+            if params.dry_run {
+                return match blocking(move || {
+                    let fee_rate = params.fee_rate.resolve_for(&data)?;
+                    let mut shiro_wallet = data.lock().unwrap();
+                    let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                    let moved = Moved::Amounts(
+                        recipient_map
+                            .iter()
+                            .map(|(asset_id, recipients)| {
+                                (
+                                    asset_id.clone(),
+                                    recipients.iter().map(|recipient| recipient.amount).sum(),
+                                )
+                            })
+                            .collect(),
+                    );
+                    DryRunResult::preview(wallet, &online, moved, |wallet, online| {
+                        wallet.send_begin(online, recipient_map, params.donation, fee_rate)
                     })
-                    .collect::<HashMap<_, _>>();
-                shiro_wallet.wallet.as_mut().unwrap().send(
-                    online,
-                    recipient_map,
-                    params.donation,
-                    params.fee_rate,
-                )
+                })
+                .await
+                {
+                    Ok(result) => HttpResponse::Ok().json(result),
+                    Err(e) => e.error_response(),
+                };
+            }
+            let watch_only = data.lock().unwrap().is_watch_only();
+            match blocking(move || {
+                let fee_rate = params.fee_rate.resolve_for(&data)?;
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                let result = if watch_only {
+                    wallet.send_begin(online, recipient_map, params.donation, fee_rate)
+                } else {
+                    wallet.send(online, recipient_map, params.donation, fee_rate)
+                };
+                Ok(result?)
             })
             .await
-            .unwrap()
             {
+                Ok(unsigned_psbt) if watch_only => {
+                    HttpResponse::Ok().json(UnsignedPsbtResult { unsigned_psbt })
+                }
                 Ok(txid) => HttpResponse::Ok().json(SendResult { txid }),
-                Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+                Err(e) => e.error_response(),
             }
         } else {
-            HttpResponse::BadRequest().body("wallet should be online")
+            ShiroError::WalletNotOnline.error_response()
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use super::*;
 
     use crate::tests::PROXY_ENDPOINT;
     use crate::wallet::{
         address::AddressResult,
+        dry_run::tests::{dir_snapshot, transfers},
         go_online::GoOnlineParams,
         issue::rgb20::{Rgb20Params, Rgb20Result},
         tests::fund_wallet,
         utxos::UtxosParams,
     };
-    use actix_web::{test, web, App};
+    use actix_web::{http, test, web, App};
     use rgb_lib::{
         generate_keys,
         wallet::{Wallet, WalletData},
     };
 
-    async fn get_blinded_utxo() -> String {
+    pub async fn get_blinded_utxo() -> String {
         let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
         let base_data = shiro_backend::opts::get_wallet_data();
         let wallet_data = WalletData {
@@ -138,10 +198,10 @@ mod tests {
 
     #[actix_web::test]
     async fn test_post() {
-        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let data = web::Data::new(Mutex::new(ShiroWallet::new()));
         let app = test::init_service(
             App::new()
-                .app_data(web::Data::new(shiro_wallet))
+                .app_data(data.clone())
                 .service(crate::wallet::put)
                 .service(crate::wallet::address::get)
                 .service(crate::wallet::utxos::put)
@@ -155,7 +215,7 @@ mod tests {
         {
             let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
             let params = crate::wallet::WalletParams {
-                mnemonic: keys.mnemonic,
+                mnemonic: Some(keys.mnemonic),
                 pubkey: keys.xpub,
             };
             let req = test::TestRequest::put()
@@ -208,19 +268,121 @@ mod tests {
             test::call_and_read_body_json(&app, req).await
         };
         let blinded_utxo = get_blinded_utxo().await;
+        let recipient_map = || {
+            let mut recipient_map = HashMap::new();
+            recipient_map.insert(
+                rgb20_result.asset_id.clone(),
+                vec![Recipient {
+                    blinded_utxo: blinded_utxo.clone(),
+                    amount: "10".to_string(),
+                    transport_endpoints: vec![PROXY_ENDPOINT.clone()],
+                }],
+            );
+            recipient_map
+        };
+        {
+            let wallet_dir = data
+                .lock()
+                .unwrap()
+                .wallet
+                .as_ref()
+                .unwrap()
+                .get_wallet_dir();
+            let dir_before = dir_snapshot(&wallet_dir);
+            let transfers_before = transfers(&data, &rgb20_result.asset_id);
+            let params = SendParams {
+                recipient_map: recipient_map(),
+                donation: false,
+                fee_rate: FeeRate::Rate(1.0),
+                dry_run: true,
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/send")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let result: DryRunResult = test::read_body_json(resp).await;
+            assert!(result.summary.fee.is_some());
+            // Only the sent amount is listed, the change stays in the wallet.
+            assert_eq!(result.spent_allocations.len(), 1);
+            assert_eq!(result.spent_allocations[0].asset_id, rgb20_result.asset_id);
+            assert_eq!(result.spent_allocations[0].amount, "10");
+            assert_eq!(
+                result.balances.assets.get(&rgb20_result.asset_id),
+                Some(&"656".to_string())
+            );
+            assert!(result.balances.btc.is_some());
+            assert!(dir_before == dir_snapshot(&wallet_dir));
+            assert_eq!(transfers_before, transfers(&data, &rgb20_result.asset_id));
+        }
+        let params = SendParams {
+            recipient_map: recipient_map(),
+            donation: false,
+            fee_rate: FeeRate::Rate(1.0),
+            dry_run: false,
+        };
+        let req = test::TestRequest::post()
+            .uri("/wallet/send")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+    }
+
+    #[actix_web::test]
+    async fn test_post_bad_amount() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::go_online::put)
+                .service(post),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
         let mut recipient_map = HashMap::new();
         recipient_map.insert(
-            rgb20_result.asset_id,
+            "rgb1".to_string(),
             vec![Recipient {
-                blinded_utxo,
-                amount: "10".to_string(),
+                blinded_utxo: "".to_string(),
+                amount: "-10".to_string(),
                 transport_endpoints: vec![PROXY_ENDPOINT.clone()],
             }],
         );
         let params = SendParams {
             recipient_map,
             donation: false,
-            fee_rate: 1.0,
+            fee_rate: FeeRate::Rate(1.0),
+            dry_run: false,
         };
         let req = test::TestRequest::post()
             .uri("/wallet/send")
@@ -228,6 +390,12 @@ mod tests {
             .to_request();
         let resp = test::call_service(&app, req).await;
         println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        // The worker is still serving requests.
+        let req = test::TestRequest::get().uri("/wallet/address").to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
         assert!(resp.status().is_success());
     }
 }
@@ -0,0 +1,178 @@
+use crate::{
+    error::ShiroError,
+    wallet::{
+        blocking,
+        send::{Recipient, SendParams},
+    },
+    ShiroWallet,
+};
+use actix_web::{post, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize)]
+pub struct SendBeginResult {
+    pub unsigned_psbt: String,
+    recipient_map: HashMap<String, Vec<Recipient>>,
+    donation: bool,
+    /// Resolved from the preset or target given in the params
+    fee_rate: f32,
+}
+
+#[post("/wallet/send/begin")]
+pub async fn post(
+    params: web::Json<SendParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if params.dry_run {
+        return ShiroError::InvalidParams(
+            "dry_run is not supported by /wallet/send/begin, see POST /wallet/send".to_string(),
+        )
+        .error_response();
+    }
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            let recipient_map = match params.get_recipient_map() {
+                Ok(recipient_map) => recipient_map,
+                Err(e) => return e.error_response(),
+            };
+            match blocking(move || {
+                let fee_rate = params.fee_rate.resolve_for(&data)?;
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                wallet
+                    .send_begin(online, recipient_map.clone(), params.donation, fee_rate)
+                    .map(|unsigned_psbt| SendBeginResult {
+                        unsigned_psbt,
+                        recipient_map: recipient_map
+                            .into_iter()
+                            .map(|(asset_id, recipients)| {
+                                (
+                                    asset_id,
+                                    recipients
+                                        .into_iter()
+                                        .map(Recipient::from)
+                                        .collect::<Vec<Recipient>>(),
+                                )
+                            })
+                            .collect::<HashMap<_, _>>(),
+                        donation: params.donation,
+                        fee_rate,
+                    })
+                    .map_err(ShiroError::from)
+            })
+            .await
+            {
+                Ok(result) => HttpResponse::Ok().json(result),
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tests::PROXY_ENDPOINT;
+    use crate::wallet::{fees::FeeRate, go_online::GoOnlineParams};
+    use actix_web::{http, test, App};
+    use rgb_lib::generate_keys;
+
+    #[actix_web::test]
+    async fn test_post_bad_amount() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::go_online::put)
+                .service(post),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let mut recipient_map = HashMap::new();
+        recipient_map.insert(
+            "rgb1".to_string(),
+            vec![Recipient {
+                blinded_utxo: "".to_string(),
+                amount: "-10".to_string(),
+                transport_endpoints: vec![PROXY_ENDPOINT.clone()],
+            }],
+        );
+        let params = SendParams {
+            recipient_map,
+            donation: false,
+            fee_rate: FeeRate::Rate(1.0),
+            dry_run: false,
+        };
+        let req = test::TestRequest::post()
+            .uri("/wallet/send/begin")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+        // The worker is still serving requests.
+        let req = test::TestRequest::get().uri("/wallet/address").to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+    }
+
+    #[actix_web::test]
+    async fn test_post_dry_run() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Mutex::new(ShiroWallet::new())))
+                .service(post),
+        )
+        .await;
+
+        let params = SendParams {
+            recipient_map: HashMap::new(),
+            donation: false,
+            fee_rate: FeeRate::Rate(1.0),
+            dry_run: true,
+        };
+        let req = test::TestRequest::post()
+            .uri("/wallet/send/begin")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+    }
+}
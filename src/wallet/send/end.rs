@@ -0,0 +1,176 @@
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, send::SendResult},
+    ShiroWallet,
+};
+use actix_web::{post, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize)]
+pub struct SendEndParams {
+    signed_psbt: String,
+}
+
+#[post("/wallet/send/end")]
+pub async fn post(
+    params: web::Json<SendEndParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            match blocking(move || {
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                Ok(wallet.send_end(online, params.signed_psbt.clone())?)
+            })
+            .await
+            {
+                Ok(txid) => HttpResponse::Ok().json(SendResult { txid }),
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tests::PROXY_ENDPOINT;
+    use crate::wallet::{
+        address::AddressResult,
+        fees::FeeRate,
+        go_online::GoOnlineParams,
+        issue::rgb20::{Rgb20Params, Rgb20Result},
+        psbt::sign::{SignParams, SignResult},
+        send::{begin::SendBeginResult, tests::get_blinded_utxo, Recipient, SendParams},
+        tests::{fund_wallet, gen_fake_ticker},
+        utxos::UtxosParams,
+    };
+    use actix_web::{test, web, App};
+    use rgb_lib::generate_keys;
+    use std::collections::HashMap;
+
+    #[actix_web::test]
+    async fn test_post() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::utxos::put)
+                .service(crate::wallet::go_online::put)
+                .service(crate::wallet::issue::rgb20::put)
+                .service(crate::wallet::send::begin::post)
+                .service(crate::wallet::psbt::sign::post)
+                .service(post),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address.clone());
+        fund_wallet(address.new_address);
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = UtxosParams::new(true, Some(2), None, 1.0);
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert!(resp.status().is_success());
+        }
+        let rgb20_result: Rgb20Result = {
+            let params = Rgb20Params {
+                ticker: gen_fake_ticker(),
+                name: "Fake Monacoin".to_string(),
+                presision: 7,
+                amounts: vec![666.to_string()],
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/issue/rgb20")
+                .set_json(params)
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+        let blinded_utxo = get_blinded_utxo().await;
+        let mut recipient_map = HashMap::new();
+        recipient_map.insert(
+            rgb20_result.asset_id,
+            vec![Recipient {
+                blinded_utxo,
+                amount: "10".to_string(),
+                transport_endpoints: vec![PROXY_ENDPOINT.clone()],
+            }],
+        );
+        let begin_result: SendBeginResult = {
+            let params = SendParams {
+                recipient_map,
+                donation: false,
+                fee_rate: FeeRate::Rate(1.0),
+                dry_run: false,
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/send/begin")
+                .set_json(params)
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+        let sign_result: SignResult = {
+            let params = SignParams {
+                unsigned_psbt: begin_result.unsigned_psbt,
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/psbt/sign")
+                .set_json(params)
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+        let params = SendEndParams {
+            signed_psbt: sign_result.signed_psbt,
+        };
+        let req = test::TestRequest::post()
+            .uri("/wallet/send/end")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{error::ShiroError, wallet::blocking, ShiroWallet};
+use actix_web::{get, web, HttpResponse, Responder, ResponseError};
+use rgb_lib::wallet::TransactionType;
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize)]
+pub struct Transaction {
+    /// `user`, `create_utxos`, `drain` or `rgb_send`
+    transaction_type: String,
+    txid: String,
+    received: String,
+    sent: String,
+    fee: Option<String>,
+    /// `None` while unconfirmed
+    confirmation_height: Option<u32>,
+    confirmation_timestamp: Option<u64>,
+}
+
+impl From<rgb_lib::wallet::Transaction> for Transaction {
+    fn from(x: rgb_lib::wallet::Transaction) -> Transaction {
+        Transaction {
+            transaction_type: match x.transaction_type {
+                TransactionType::User => "user",
+                TransactionType::CreateUtxos => "create_utxos",
+                TransactionType::Drain => "drain",
+                TransactionType::RgbSend => "rgb_send",
+            }
+            .to_string(),
+            txid: x.txid,
+            received: x.received.to_string(),
+            sent: x.sent.to_string(),
+            fee: x.fee.map(|fee| fee.to_string()),
+            confirmation_height: x.confirmation_time.as_ref().map(|time| time.height),
+            confirmation_timestamp: x.confirmation_time.as_ref().map(|time| time.timestamp),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TransactionsResult {
+    transactions: Vec<Transaction>,
+}
+
+/// Syncs with the Electrum server first when the wallet is online.
+#[get("/wallet/transactions")]
+pub async fn get(data: web::Data<Mutex<ShiroWallet>>) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        match blocking(move || {
+            let mut shiro_wallet = data.lock().unwrap();
+            let online = shiro_wallet.get_online();
+            Ok(shiro_wallet.wallet_mut()?.list_transactions(online)?)
+        })
+        .await
+        {
+            Ok(transactions) => HttpResponse::Ok().json(TransactionsResult {
+                transactions: transactions
+                    .into_iter()
+                    .map(Transaction::from)
+                    .collect::<Vec<Transaction>>(),
+            }),
+            Err(e) => e.error_response(),
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::{
+        address::AddressResult, go_online::GoOnlineParams, tests::fund_wallet, utxos::UtxosParams,
+    };
+    use actix_web::{test, App};
+    use rgb_lib::generate_keys;
+
+    #[actix_web::test]
+    async fn test_get() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(get)
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::utxos::put)
+                .service(crate::wallet::go_online::put),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address);
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = UtxosParams::new(false, Some(1), None, 1.0);
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let req = test::TestRequest::get()
+                .uri("/wallet/transactions")
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let result: TransactionsResult = test::read_body_json(resp).await;
+            let types = result
+                .transactions
+                .iter()
+                .map(|x| x.transaction_type.as_str())
+                .collect::<Vec<&str>>();
+            assert!(types.contains(&"user"));
+            assert!(types.contains(&"create_utxos"));
+        }
+    }
+}
@@ -1,5 +1,5 @@
-use crate::ShiroWallet;
-use actix_web::{delete, put, web, HttpResponse, Responder};
+use crate::{error::ShiroError, wallet::blocking, ShiroWallet};
+use actix_web::{delete, put, web, HttpResponse, Responder, ResponseError};
 use rgb_lib::{
     wallet::{Outpoint, TransferKind},
     TransferStatus,
@@ -8,6 +8,8 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
 
+pub mod fail;
+
 #[derive(Serialize, Deserialize)]
 pub struct TransferParams {
     asset_id: String,
@@ -29,19 +31,22 @@ pub struct Transfer {
     expiration: Option<String>,
 }
 
+pub fn status_name(status: &TransferStatus) -> &'static str {
+    match status {
+        TransferStatus::WaitingCounterparty => "WaitingCounterparty",
+        TransferStatus::WaitingConfirmations => "WaitingConfirmations",
+        TransferStatus::Settled => "Settled",
+        TransferStatus::Failed => "Failed",
+    }
+}
+
 impl From<rgb_lib::wallet::Transfer> for Transfer {
     fn from(x: rgb_lib::wallet::Transfer) -> Transfer {
         Transfer {
             idx: x.idx.to_string(),
             created_at: x.created_at.to_string(),
             updated_at: x.updated_at.to_string(),
-            status: match x.status {
-                TransferStatus::WaitingCounterparty => "WaitingCounterparty",
-                TransferStatus::WaitingConfirmations => "WaitingConfirmations",
-                TransferStatus::Settled => "Settled",
-                TransferStatus::Failed => "Failed",
-            }
-            .to_string(),
+            status: status_name(&x.status).to_string(),
             amount: x.amount.to_string(),
             kind: match x.kind {
                 TransferKind::Issuance => "issuance",
@@ -70,16 +75,13 @@ pub async fn put(
     data: web::Data<Mutex<ShiroWallet>>,
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
-        match actix_web::rt::task::spawn_blocking(move || {
-            data.lock()
-                .unwrap()
-                .wallet
-                .as_mut()
-                .unwrap()
-                .list_transfers(params.asset_id.clone())
+        match blocking(move || {
+            let mut shiro_wallet = data.lock().unwrap();
+            Ok(shiro_wallet
+                .wallet_mut()?
+                .list_transfers(params.asset_id.clone())?)
         })
         .await
-        .unwrap()
         {
             Ok(transfers) => HttpResponse::Ok().json(
                 transfers
@@ -87,23 +89,23 @@ pub async fn put(
                     .map(Transfer::from)
                     .collect::<Vec<Transfer>>(),
             ),
-            Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+            Err(e) => e.error_response(),
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct TransferDeleteParams {
-    blinded_utxo: Option<String>,
-    txid: Option<String>,
-    no_asset_only: bool,
+    pub blinded_utxo: Option<String>,
+    pub txid: Option<String>,
+    pub no_asset_only: bool,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct TransferDeleteResult {
-    transfers_changed: bool,
+    pub transfers_changed: bool,
 }
 
 #[delete("/wallet/transfers")]
@@ -112,28 +114,23 @@ pub async fn delete(
     data: web::Data<Mutex<ShiroWallet>>,
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
-        match actix_web::rt::task::spawn_blocking(move || {
-            data.lock()
-                .unwrap()
-                .wallet
-                .as_mut()
-                .unwrap()
-                .delete_transfers(
-                    params.blinded_utxo.clone(),
-                    params.txid.clone(),
-                    params.no_asset_only,
-                )
+        match blocking(move || {
+            let mut shiro_wallet = data.lock().unwrap();
+            Ok(shiro_wallet.wallet_mut()?.delete_transfers(
+                params.blinded_utxo.clone(),
+                params.txid.clone(),
+                params.no_asset_only,
+            )?)
         })
         .await
-        .unwrap()
         {
             Ok(transfers_changed) => {
                 HttpResponse::Ok().json(TransferDeleteResult { transfers_changed })
             }
-            Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+            Err(e) => e.error_response(),
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
@@ -169,7 +166,7 @@ mod tests {
         {
             let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
             let params = crate::wallet::WalletParams {
-                mnemonic: keys.mnemonic,
+                mnemonic: Some(keys.mnemonic),
                 pubkey: keys.xpub,
             };
             let req = test::TestRequest::put()
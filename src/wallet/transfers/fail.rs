@@ -0,0 +1,273 @@
+use crate::{error::ShiroError, wallet::blocking, ShiroWallet};
+use actix_web::{post, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Without `blinded_utxo` nor `txid`, every expired `WaitingCounterparty` transfer is failed.
+#[derive(Deserialize, Serialize)]
+pub struct TransferFailParams {
+    #[serde(default)]
+    blinded_utxo: Option<String>,
+    #[serde(default)]
+    txid: Option<String>,
+    #[serde(default)]
+    no_asset_only: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TransferFailResult {
+    pub transfers_changed: bool,
+}
+
+/// Fails the expired transfers of an online wallet, `None` when there is nothing to do.
+/// Blocks, to be called from `spawn_blocking`.
+pub fn fail_expired(data: &Mutex<ShiroWallet>) -> Option<Result<bool, ShiroError>> {
+    let mut shiro_wallet = data.lock().unwrap();
+    let online = shiro_wallet.get_online()?;
+    let wallet = shiro_wallet.wallet.as_mut()?;
+    Some(
+        wallet
+            .fail_transfers(online, None, None, false)
+            .map_err(ShiroError::from),
+    )
+}
+
+#[post("/wallet/transfers/fail")]
+pub async fn post(
+    params: web::Json<TransferFailParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            match blocking(move || {
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                Ok(wallet.fail_transfers(
+                    online,
+                    params.blinded_utxo.clone(),
+                    params.txid.clone(),
+                    params.no_asset_only,
+                )?)
+            })
+            .await
+            {
+                Ok(transfers_changed) => {
+                    HttpResponse::Ok().json(TransferFailResult { transfers_changed })
+                }
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tests::PROXY_ENDPOINT;
+    use crate::wallet::{
+        address::AddressResult,
+        go_online::GoOnlineParams,
+        issue::rgb20::{Rgb20Params, Rgb20Result},
+        send::tests::get_blinded_utxo,
+        tests::{fund_wallet, gen_fake_ticker},
+        transfers::{TransferDeleteParams, TransferDeleteResult},
+        utxos::UtxosParams,
+    };
+    use actix_web::{http, test, App};
+    use rgb_lib::generate_keys;
+    use serde_json::json;
+
+    #[actix_web::test]
+    async fn test_post() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(crate::wallet::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::utxos::put)
+                .service(crate::wallet::go_online::put)
+                .service(crate::wallet::blind::put)
+                .service(crate::wallet::issue::rgb20::put)
+                .service(crate::wallet::send::post)
+                .service(crate::wallet::transfers::delete)
+                .service(post),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let req = test::TestRequest::post()
+                .uri("/wallet/transfers/fail")
+                .set_json(json!({}))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::PRECONDITION_FAILED);
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address);
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = UtxosParams::new(true, Some(3), None, 1.0);
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let blinded_utxo = {
+            let params = json!({
+                "asset_id": null,
+                "amount": null,
+                "duration_seconds": 1,
+                "transport_endpoints": [PROXY_ENDPOINT.clone()],
+            });
+            let req = test::TestRequest::put()
+                .uri("/wallet/blind")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            body["blinded_utxo"].as_str().unwrap().to_string()
+        };
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        {
+            let req = test::TestRequest::post()
+                .uri("/wallet/transfers/fail")
+                .set_json(json!({}))
+                .to_request();
+            let result: TransferFailResult = test::call_and_read_body_json(&app, req).await;
+            assert!(result.transfers_changed);
+        }
+        // Failed transfers can then be deleted.
+        {
+            let params = TransferDeleteParams {
+                blinded_utxo: Some(blinded_utxo),
+                txid: None,
+                no_asset_only: false,
+            };
+            let req = test::TestRequest::delete()
+                .uri("/wallet/transfers")
+                .set_json(params)
+                .to_request();
+            let result: TransferDeleteResult = test::call_and_read_body_json(&app, req).await;
+            assert!(result.transfers_changed);
+        }
+        // A receive is failed by its blinded UTXO before it expires.
+        let blinded_utxo = {
+            let params = json!({
+                "asset_id": null,
+                "amount": null,
+                "duration_seconds": null,
+                "transport_endpoints": [PROXY_ENDPOINT.clone()],
+            });
+            let req = test::TestRequest::put()
+                .uri("/wallet/blind")
+                .set_json(params)
+                .to_request();
+            let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+            body["blinded_utxo"].as_str().unwrap().to_string()
+        };
+        {
+            let req = test::TestRequest::post()
+                .uri("/wallet/transfers/fail")
+                .set_json(json!({ "blinded_utxo": blinded_utxo }))
+                .to_request();
+            let result: TransferFailResult = test::call_and_read_body_json(&app, req).await;
+            assert!(result.transfers_changed);
+        }
+        // An unknown txid is refused.
+        {
+            let req = test::TestRequest::post()
+                .uri("/wallet/transfers/fail")
+                .set_json(json!({ "txid": "0".repeat(64) }))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_client_error());
+        }
+        let rgb20_result: Rgb20Result = {
+            let params = Rgb20Params {
+                ticker: gen_fake_ticker(),
+                name: "Fake Monacoin".to_string(),
+                presision: 7,
+                amounts: vec![666.to_string()],
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/issue/rgb20")
+                .set_json(params)
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+        // A send waiting for the recipient is failed by its txid.
+        let txid = {
+            let params = json!({
+                "recipient_map": {
+                    rgb20_result.asset_id: [{
+                        "blinded_utxo": get_blinded_utxo().await,
+                        "amount": "10",
+                        "transport_endpoints": [PROXY_ENDPOINT.clone()],
+                    }],
+                },
+                "donation": false,
+                "fee_rate": 1.0,
+            });
+            let req = test::TestRequest::post()
+                .uri("/wallet/send")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            body["txid"].as_str().unwrap().to_string()
+        };
+        {
+            let req = test::TestRequest::post()
+                .uri("/wallet/transfers/fail")
+                .set_json(json!({ "txid": txid }))
+                .to_request();
+            let result: TransferFailResult = test::call_and_read_body_json(&app, req).await;
+            assert!(result.transfers_changed);
+        }
+    }
+}
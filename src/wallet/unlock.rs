@@ -0,0 +1,405 @@
+use crate::{
+    error::ShiroError,
+    wallet::{
+        blocking, data::mnemonic::RevealLimiter, events::WalletEvent, keystore::Keystore,
+        record::WalletRecord,
+    },
+    ShiroWallet,
+};
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder, ResponseError};
+use rgb_lib::wallet::{Wallet, WalletData};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Instant;
+
+#[derive(Serialize, Deserialize)]
+pub struct UnlockParams {
+    pub passphrase: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UnlockResult {}
+
+/// Opens the wallet from its keystore, online again if it was when locked. Attempts share the
+/// limit of `POST /wallet/data/mnemonic`, since both take the passphrase.
+#[post("/wallet/unlock")]
+pub async fn post(
+    req: HttpRequest,
+    params: web::Json<UnlockParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+    limiter: web::Data<RevealLimiter>,
+) -> impl Responder {
+    let (data_dir, electrum_url) = {
+        let shiro_wallet = data.lock().unwrap();
+        if shiro_wallet.wallet.is_some() {
+            return HttpResponse::Ok().json(UnlockResult {});
+        }
+        let data_dir = shiro_wallet.get_data_dir();
+        // After a restart, the record kept by `--reopen-wallets` knows the Electrum server.
+        let electrum_url = shiro_wallet.locked_electrum_url.clone().or_else(|| {
+            WalletRecord::load(&data_dir)
+                .ok()
+                .flatten()
+                .and_then(|record| record.electrum_url)
+        });
+        (data_dir, electrum_url)
+    };
+    let keystore = match Keystore::load(&data_dir) {
+        Ok(Some(keystore)) => keystore,
+        Ok(None) => return ShiroError::PassphraseNotSet.error_response(),
+        Err(e) => return ShiroError::Internal(e.to_string()).error_response(),
+    };
+    let peer = req.peer_addr().map(|x| x.ip().to_string());
+    if !limiter.try_attempt(&data_dir, peer.as_deref()) {
+        crate::audit::log(&data_dir, "unlock", peer, "rate-limited");
+        return ShiroError::TooManyRequests.error_response();
+    }
+    let wallet_data_dir = data_dir.clone();
+    let skip_consistency_check = shiro_backend::opts::get_args().skip_consistency_check;
+    match blocking(move || {
+        let mnemonic = keystore.decrypt(&params.passphrase)?;
+        let base_data = shiro_backend::opts::get_wallet_data();
+        let mut wallet = Wallet::new(WalletData {
+            data_dir: wallet_data_dir,
+            bitcoin_network: base_data.bitcoin_network,
+            database_type: base_data.database_type,
+            pubkey: keystore.pubkey,
+            mnemonic: Some(mnemonic),
+        })?;
+        let online = match electrum_url {
+            Some(electrum_url) => match wallet.go_online(skip_consistency_check, electrum_url) {
+                Ok(online) => Some(online),
+                Err(e) => {
+                    println!("the unlocked wallet stays offline: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        Ok((wallet, online))
+    })
+    .await
+    {
+        Ok((wallet, online)) => {
+            crate::audit::log(&data_dir, "unlock", peer, "unlocked");
+            let mut shiro_wallet = data.lock().unwrap();
+            if shiro_wallet.wallet.is_none() {
+                shiro_wallet.wallet = Some(wallet);
+                if let Some(online) = &online {
+                    shiro_wallet.events.notify(WalletEvent::Online {
+                        electrum_url: online.electrum_url.clone(),
+                    });
+                }
+                shiro_wallet.online = online;
+                shiro_wallet.locked_electrum_url = None;
+                shiro_wallet.last_used = Instant::now();
+            }
+            HttpResponse::Ok().json(UnlockResult {})
+        }
+        Err(e) => {
+            let outcome = match e {
+                ShiroError::WrongPassphrase => "denied",
+                _ => "failed",
+            };
+            crate::audit::log(&data_dir, "unlock", peer, outcome);
+            e.error_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::{go_online::GoOnlineParams, passphrase::PassphraseParams};
+    use actix_web::{http, test, App};
+    use rgb_lib::generate_keys;
+
+    #[actix_web::test]
+    async fn test_lock_unlock() {
+        let data_dir = std::env::temp_dir().join(format!(
+            "shiro-unlock-test-{}",
+            hex::encode(rand::random::<[u8; 4]>())
+        ));
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let data = web::Data::new(Mutex::new(ShiroWallet::with_data_dir(
+            data_dir.to_string_lossy().to_string(),
+        )));
+        let app = test::init_service(
+            App::new()
+                .app_data(data.clone())
+                .app_data(web::Data::new(RevealLimiter::new()))
+                .service(crate::wallet::put)
+                .service(crate::wallet::go_online::put)
+                .service(crate::wallet::passphrase::put)
+                .service(crate::wallet::lock::post)
+                .service(crate::wallet::address::get)
+                .service(post),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let req = test::TestRequest::post().uri("/wallet/lock").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::PRECONDITION_FAILED);
+        }
+        {
+            let params = PassphraseParams {
+                passphrase: "correct horse battery staple".to_string(),
+                old_passphrase: None,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/passphrase")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            // Changing it needs the current one.
+            let params = PassphraseParams {
+                passphrase: "hijacked passphrase".to_string(),
+                old_passphrase: None,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/passphrase")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+        }
+        {
+            let params = PassphraseParams {
+                passphrase: "hijacked passphrase".to_string(),
+                old_passphrase: Some("wrong passphrase".to_string()),
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/passphrase")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+        }
+        {
+            let params = PassphraseParams {
+                passphrase: "battery staple correct horse".to_string(),
+                old_passphrase: Some("correct horse battery staple".to_string()),
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/passphrase")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let req = test::TestRequest::post().uri("/wallet/lock").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            assert!(data.lock().unwrap().wallet.is_none());
+            assert!(data.lock().unwrap().online.is_none());
+        }
+        {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::PRECONDITION_FAILED);
+        }
+        {
+            let params = UnlockParams {
+                passphrase: "correct horse battery staple".to_string(),
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/unlock")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+        }
+        {
+            let params = UnlockParams {
+                passphrase: "battery staple correct horse".to_string(),
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/unlock")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            // Back online, as it was when locked.
+            assert!(data.lock().unwrap().online.is_some());
+        }
+        {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let audit_log = std::fs::read_to_string(data_dir.join("audit.log")).unwrap();
+        assert!(audit_log.contains("unlock peer=unknown denied"));
+        assert!(audit_log.contains("unlock peer=unknown unlocked"));
+        assert!(audit_log.contains("change_passphrase peer=unknown denied"));
+        assert!(audit_log.contains("change_passphrase peer=unknown changed"));
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_unlock_rate_limited() {
+        let data_dir = std::env::temp_dir().join(format!(
+            "shiro-unlock-test-{}",
+            hex::encode(rand::random::<[u8; 4]>())
+        ));
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+        Keystore::encrypt(keys.xpub, &keys.mnemonic, "correct horse battery staple")
+            .unwrap()
+            .save(&data_dir.to_string_lossy())
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Mutex::new(ShiroWallet::with_data_dir(
+                    data_dir.to_string_lossy().to_string(),
+                ))))
+                .app_data(web::Data::new(RevealLimiter::new()))
+                .service(post),
+        )
+        .await;
+
+        let mut statuses = vec![];
+        for _ in 0..6 {
+            let params = UnlockParams {
+                passphrase: "wrong passphrase".to_string(),
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/unlock")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            statuses.push(resp.status());
+        }
+        assert_eq!(statuses[4], http::StatusCode::FORBIDDEN);
+        assert_eq!(statuses[5], http::StatusCode::TOO_MANY_REQUESTS);
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_passphrase_rate_limited() {
+        let data_dir = std::env::temp_dir().join(format!(
+            "shiro-unlock-test-{}",
+            hex::encode(rand::random::<[u8; 4]>())
+        ));
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Mutex::new(ShiroWallet::with_data_dir(
+                    data_dir.to_string_lossy().to_string(),
+                ))))
+                .app_data(web::Data::new(RevealLimiter::new()))
+                .service(crate::wallet::put)
+                .service(crate::wallet::passphrase::put)
+                .service(crate::wallet::lock::post)
+                .service(post),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = PassphraseParams {
+                passphrase: "correct horse battery staple".to_string(),
+                old_passphrase: None,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/passphrase")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let mut statuses = vec![];
+        for _ in 0..6 {
+            let params = PassphraseParams {
+                passphrase: "hijacked passphrase".to_string(),
+                old_passphrase: Some("wrong passphrase".to_string()),
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/passphrase")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            statuses.push(resp.status());
+        }
+        assert_eq!(statuses[4], http::StatusCode::FORBIDDEN);
+        assert_eq!(statuses[5], http::StatusCode::TOO_MANY_REQUESTS);
+        // The guesses count against unlocking too.
+        {
+            let req = test::TestRequest::post().uri("/wallet/lock").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let params = UnlockParams {
+                passphrase: "correct horse battery staple".to_string(),
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/unlock")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+        }
+        let audit_log = std::fs::read_to_string(data_dir.join("audit.log")).unwrap();
+        assert!(audit_log.contains("change_passphrase peer=unknown rate-limited"));
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+}
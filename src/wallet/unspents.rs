@@ -1,5 +1,5 @@
-use crate::ShiroWallet;
-use actix_web::{put, web, HttpResponse, Responder};
+use crate::{error::ShiroError, wallet::blocking, ShiroWallet};
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
 use rgb_lib::wallet::Outpoint;
 use serde::Deserialize;
 use serde::Serialize;
@@ -45,7 +45,7 @@ impl From<rgb_lib::wallet::RgbAllocation> for RgbAllocation {
 }
 #[derive(Serialize, Deserialize)]
 pub struct Unspent {
-    utxo: Utxo,
+    pub utxo: Utxo,
     rgb_allocations: Vec<RgbAllocation>,
 }
 
@@ -64,7 +64,7 @@ impl Unspent {
 
 #[derive(Serialize, Deserialize)]
 pub struct UnspentsResult {
-    unspents: Vec<Unspent>,
+    pub unspents: Vec<Unspent>,
 }
 
 #[put("/wallet/unspents")]
@@ -73,16 +73,14 @@ pub async fn put(
     data: web::Data<Mutex<ShiroWallet>>,
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
-        match actix_web::rt::task::spawn_blocking(move || {
-            data.lock()
+        match blocking(move || {
+            Ok(data
+                .lock()
                 .unwrap()
-                .wallet
-                .as_mut()
-                .unwrap()
-                .list_unspents(params.settled_only)
+                .wallet_mut()?
+                .list_unspents(params.settled_only)?)
         })
         .await
-        .unwrap()
         {
             Ok(unspents) => HttpResponse::Ok().json(UnspentsResult {
                 unspents: unspents
@@ -90,10 +88,10 @@ pub async fn put(
                     .map(Unspent::from)
                     .collect::<Vec<Unspent>>(),
             }),
-            Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+            Err(e) => e.error_response(),
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
@@ -118,7 +116,7 @@ mod tests {
         {
             let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
             let wallet_params = crate::wallet::WalletParams {
-                mnemonic: keys.mnemonic,
+                mnemonic: Some(keys.mnemonic),
                 pubkey: keys.xpub,
             };
             let req = test::TestRequest::put()
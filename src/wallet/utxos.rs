@@ -1,15 +1,30 @@
-use crate::ShiroWallet;
-use actix_web::{put, web, HttpResponse, Responder};
+use crate::{
+    error::ShiroError,
+    wallet::{
+        blocking,
+        dry_run::{DryRunResult, Moved},
+        fees::FeeRate,
+        UnsignedPsbtResult,
+    },
+    ShiroWallet,
+};
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Mutex;
 
+pub mod begin;
+pub mod end;
+
 #[derive(Serialize, Deserialize)]
 pub struct UtxosParams {
     up_to: bool,
     num: Option<u8>,
     size: Option<u32>,
-    fee_rate: f32,
+    fee_rate: FeeRate,
+    /// Only return what the transaction would be, see [`DryRunResult`]
+    #[serde(default)]
+    dry_run: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -24,28 +39,64 @@ pub async fn put(
 ) -> impl Responder {
     if data.lock().unwrap().wallet.is_some() {
         if data.lock().unwrap().online.is_some() {
-            match actix_web::rt::task::spawn_blocking(move || {
+            if params.dry_run {
+                return match blocking(move || {
+                    let fee_rate = params.fee_rate.resolve_for(&data)?;
+                    let mut shiro_wallet = data.lock().unwrap();
+                    let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                    DryRunResult::preview(wallet, &online, Moved::Nothing, |wallet, online| {
+                        wallet.create_utxos_begin(
+                            online,
+                            params.up_to,
+                            params.num,
+                            params.size,
+                            fee_rate,
+                        )
+                    })
+                })
+                .await
+                {
+                    Ok(result) => HttpResponse::Ok().json(result),
+                    Err(e) => e.error_response(),
+                };
+            }
+            if data.lock().unwrap().is_watch_only() {
+                return match blocking(move || {
+                    let fee_rate = params.fee_rate.resolve_for(&data)?;
+                    let mut shiro_wallet = data.lock().unwrap();
+                    let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                    Ok(wallet.create_utxos_begin(
+                        online,
+                        params.up_to,
+                        params.num,
+                        params.size,
+                        fee_rate,
+                    )?)
+                })
+                .await
+                {
+                    Ok(unsigned_psbt) => {
+                        HttpResponse::Ok().json(UnsignedPsbtResult { unsigned_psbt })
+                    }
+                    Err(e) => e.error_response(),
+                };
+            }
+            match blocking(move || {
+                let fee_rate = params.fee_rate.resolve_for(&data)?;
                 let mut shiro_wallet = data.lock().unwrap();
-                let online = shiro_wallet.get_online().unwrap();
-                shiro_wallet.wallet.as_mut().unwrap().create_utxos(
-                    online,
-                    params.up_to,
-                    params.num,
-                    params.size,
-                    params.fee_rate,
-                )
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                Ok(wallet.create_utxos(online, params.up_to, params.num, params.size, fee_rate)?)
             })
             .await
-            .unwrap()
             {
                 Ok(created_utxos) => HttpResponse::Ok().json(UtxosResult { created_utxos }),
-                Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+                Err(e) => e.error_response(),
             }
         } else {
-            HttpResponse::BadRequest().body("wallet should be online")
+            ShiroError::WalletNotOnline.error_response()
         }
     } else {
-        HttpResponse::BadRequest().body("wallet should be created first")
+        ShiroError::WalletNotCreated.error_response()
     }
 }
 
@@ -53,7 +104,10 @@ pub async fn put(
 mod tests {
     use super::*;
 
-    use crate::wallet::{address::AddressResult, go_online::GoOnlineParams, tests::fund_wallet};
+    use crate::wallet::{
+        address::AddressResult, dry_run::tests::dir_snapshot, go_online::GoOnlineParams,
+        tests::fund_wallet,
+    };
     use actix_web::{test, web, App};
     use rgb_lib::generate_keys;
 
@@ -63,17 +117,18 @@ mod tests {
                 up_to,
                 num,
                 size,
-                fee_rate,
+                fee_rate: FeeRate::Rate(fee_rate),
+                dry_run: false,
             }
         }
     }
 
     #[actix_web::test]
     async fn test_put() {
-        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let data = web::Data::new(Mutex::new(ShiroWallet::new()));
         let app = test::init_service(
             App::new()
-                .app_data(web::Data::new(shiro_wallet))
+                .app_data(data.clone())
                 .service(put)
                 .service(crate::wallet::go_online::put)
                 .service(crate::wallet::address::get)
@@ -84,7 +139,7 @@ mod tests {
         {
             let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
             let params = crate::wallet::WalletParams {
-                mnemonic: keys.mnemonic,
+                mnemonic: Some(keys.mnemonic),
                 pubkey: keys.xpub,
             };
             let req = test::TestRequest::put()
@@ -113,11 +168,45 @@ mod tests {
             println!("{:?}", resp);
             assert!(resp.status().is_success());
         }
+        let wallet_dir = data
+            .lock()
+            .unwrap()
+            .wallet
+            .as_ref()
+            .unwrap()
+            .get_wallet_dir();
+        let before = dir_snapshot(&wallet_dir);
+        {
+            let params = UtxosParams {
+                up_to: true,
+                num: Some(1),
+                size: None,
+                fee_rate: FeeRate::Preset(crate::wallet::fees::FeePreset::Normal),
+                dry_run: true,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            let result: DryRunResult = test::read_body_json(resp).await;
+            assert!(result.summary.fee.is_some());
+            assert!(result.summary.vsize > 0);
+            assert!(result.spent_allocations.is_empty());
+            assert!(result.balances.btc.is_some());
+            assert!(result.balances.assets.is_empty());
+        }
+        // The addresses derived by the preview don't reach the wallet.
+        assert!(before == dir_snapshot(&wallet_dir));
+        // Nothing was broadcast, the UTXO still has to be created.
         let params = UtxosParams {
             up_to: true,
             num: Some(1),
             size: None,
-            fee_rate: 1.0,
+            fee_rate: FeeRate::Preset(crate::wallet::fees::FeePreset::Normal),
+            dry_run: false,
         };
         let req = test::TestRequest::put()
             .uri("/wallet/utxos")
@@ -126,5 +215,7 @@ mod tests {
         let resp = test::call_service(&app, req).await;
         println!("{:?}", resp);
         assert!(resp.status().is_success());
+        let result: UtxosResult = test::read_body_json(resp).await;
+        assert_eq!(result.created_utxos, 1);
     }
 }
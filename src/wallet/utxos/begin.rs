@@ -0,0 +1,93 @@
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, psbt::PsbtSummary, utxos::UtxosParams},
+    ShiroWallet,
+};
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize)]
+pub struct UtxosBeginResult {
+    pub unsigned_psbt: String,
+    pub summary: PsbtSummary,
+}
+
+#[put("/wallet/utxos/begin")]
+pub async fn put(
+    params: web::Json<UtxosParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if params.dry_run {
+        return ShiroError::InvalidParams(
+            "dry_run is not supported by /wallet/utxos/begin, see PUT /wallet/utxos".to_string(),
+        )
+        .error_response();
+    }
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            let bitcoin_network = shiro_backend::opts::get_bitcoin_network();
+            match blocking(move || {
+                let fee_rate = params.fee_rate.resolve_for(&data)?;
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                Ok(wallet.create_utxos_begin(
+                    online,
+                    params.up_to,
+                    params.num,
+                    params.size,
+                    fee_rate,
+                )?)
+            })
+            .await
+            {
+                Ok(unsigned_psbt) => match PsbtSummary::new(&unsigned_psbt, bitcoin_network) {
+                    Ok(summary) => HttpResponse::Ok().json(UtxosBeginResult {
+                        unsigned_psbt,
+                        summary,
+                    }),
+                    Err(e) => ShiroError::Internal(e).error_response(),
+                },
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::fees::FeeRate;
+    use actix_web::{http, test, App};
+
+    #[actix_web::test]
+    async fn test_put_dry_run() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Mutex::new(ShiroWallet::new())))
+                .service(put),
+        )
+        .await;
+
+        let params = UtxosParams {
+            up_to: true,
+            num: Some(1),
+            size: None,
+            fee_rate: FeeRate::Rate(1.0),
+            dry_run: true,
+        };
+        let req = test::TestRequest::put()
+            .uri("/wallet/utxos/begin")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, utxos::UtxosResult},
+    ShiroWallet,
+};
+use actix_web::{put, web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize)]
+pub struct UtxosEndParams {
+    signed_psbt: String,
+}
+
+#[put("/wallet/utxos/end")]
+pub async fn put(
+    params: web::Json<UtxosEndParams>,
+    data: web::Data<Mutex<ShiroWallet>>,
+) -> impl Responder {
+    if data.lock().unwrap().wallet.is_some() {
+        if data.lock().unwrap().online.is_some() {
+            match blocking(move || {
+                let mut shiro_wallet = data.lock().unwrap();
+                let (online, wallet) = shiro_wallet.online_wallet_mut()?;
+                Ok(wallet.create_utxos_end(online, params.signed_psbt.clone())?)
+            })
+            .await
+            {
+                Ok(created_utxos) => HttpResponse::Ok().json(UtxosResult { created_utxos }),
+                Err(e) => e.error_response(),
+            }
+        } else {
+            ShiroError::WalletNotOnline.error_response()
+        }
+    } else {
+        ShiroError::WalletNotCreated.error_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::{
+        address::AddressResult,
+        go_online::GoOnlineParams,
+        psbt::sign::{SignParams, SignResult},
+        tests::fund_wallet,
+        utxos::{begin::UtxosBeginResult, UtxosParams},
+    };
+    use actix_web::{test, web, App};
+    use rgb_lib::generate_keys;
+
+    #[actix_web::test]
+    async fn test_put() {
+        let shiro_wallet = Mutex::new(ShiroWallet::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(shiro_wallet))
+                .service(put)
+                .service(crate::wallet::utxos::begin::put)
+                .service(crate::wallet::psbt::sign::post)
+                .service(crate::wallet::go_online::put)
+                .service(crate::wallet::address::get)
+                .service(crate::wallet::put),
+        )
+        .await;
+
+        {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = crate::wallet::WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri("/wallet")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let address: AddressResult = {
+            let req = test::TestRequest::get().uri("/wallet/address").to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+            test::read_body_json(resp).await
+        };
+        fund_wallet(address.new_address.clone());
+        {
+            let params = GoOnlineParams::new(true, "127.0.0.1:50001".to_string());
+            let req = test::TestRequest::put()
+                .uri("/wallet/go_online")
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let begin_result: UtxosBeginResult = {
+            let params = UtxosParams::new(true, Some(1), None, 1.0);
+            let req = test::TestRequest::put()
+                .uri("/wallet/utxos/begin")
+                .set_json(params)
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+        assert!(!begin_result.summary.inputs.is_empty());
+        assert!(begin_result.summary.fee.is_some());
+        let sign_result: SignResult = {
+            let params = SignParams {
+                unsigned_psbt: begin_result.unsigned_psbt,
+            };
+            let req = test::TestRequest::post()
+                .uri("/wallet/psbt/sign")
+                .set_json(params)
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+        let params = UtxosEndParams {
+            signed_psbt: sign_result.signed_psbt,
+        };
+        let req = test::TestRequest::put()
+            .uri("/wallet/utxos/end")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(resp.status().is_success());
+    }
+}
@@ -0,0 +1,514 @@
+use crate::{
+    error::ShiroError,
+    wallet::{blocking, ShiroWallet},
+};
+use actix_web::body::EitherBody;
+use actix_web::dev::{
+    forward_ready, Extensions, Service, ServiceRequest, ServiceResponse, Transform,
+};
+use actix_web::http::uri::{PathAndQuery, Uri};
+use actix_web::http::Method;
+use actix_web::{delete, get, web, Error, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::{ready, Future, Ready};
+use std::path::Path;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Wallets created through `/wallets/{wallet_id}`, each one in its own data subdirectory.
+pub struct WalletRegistry {
+    data_dir: String,
+    /// See `ShiroWallet::reopen_wallets`
+    reopen_wallets: bool,
+    wallets: Mutex<HashMap<String, web::Data<Mutex<ShiroWallet>>>>,
+}
+
+impl WalletRegistry {
+    pub fn new(data_dir: String, reopen_wallets: bool) -> WalletRegistry {
+        WalletRegistry {
+            data_dir,
+            reopen_wallets,
+            wallets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get_wallet_data_dir(&self, wallet_id: &str) -> String {
+        Path::new(&self.data_dir)
+            .join("wallets")
+            .join(wallet_id)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Also loads, without opening it, a wallet that only exists on disk.
+    pub fn get(&self, wallet_id: &str) -> Option<web::Data<Mutex<ShiroWallet>>> {
+        if let Some(shiro_wallet) = self.wallets.lock().unwrap().get(wallet_id) {
+            return Some(shiro_wallet.clone());
+        }
+        if Path::new(&self.get_wallet_data_dir(wallet_id)).is_dir() {
+            self.get_or_insert(wallet_id).ok()
+        } else {
+            None
+        }
+    }
+
+    pub fn get_or_insert(&self, wallet_id: &str) -> std::io::Result<web::Data<Mutex<ShiroWallet>>> {
+        let mut wallets = self.wallets.lock().unwrap();
+        if let Some(shiro_wallet) = wallets.get(wallet_id) {
+            return Ok(shiro_wallet.clone());
+        }
+        let data_dir = self.get_wallet_data_dir(wallet_id);
+        std::fs::create_dir_all(&data_dir)?;
+        let mut shiro_wallet = ShiroWallet::with_data_dir(data_dir);
+        shiro_wallet.reopen_wallets = self.reopen_wallets;
+        let shiro_wallet = web::Data::new(Mutex::new(shiro_wallet));
+        wallets.insert(wallet_id.to_string(), shiro_wallet.clone());
+        Ok(shiro_wallet)
+    }
+
+    /// Reopens the wallets found in `{data_dir}/wallets` that have a record.
+    pub fn reopen(&self, skip_consistency_check: bool) {
+        let entries = match std::fs::read_dir(Path::new(&self.data_dir).join("wallets")) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let wallet_id = entry.file_name().to_string_lossy().to_string();
+            if !is_valid_wallet_id(&wallet_id) || !entry.path().is_dir() {
+                continue;
+            }
+            let shiro_wallet = match self.get_or_insert(&wallet_id) {
+                Ok(shiro_wallet) => shiro_wallet,
+                Err(e) => {
+                    println!("failed to reopen wallet {}: {}", wallet_id, e);
+                    continue;
+                }
+            };
+            let result = shiro_wallet.lock().unwrap().reopen(skip_consistency_check);
+            match result {
+                Ok(true) => println!("wallet {} has been reopened", wallet_id),
+                Ok(false) => {}
+                Err(e) => println!("failed to reopen wallet {}: {}", wallet_id, e),
+            }
+        }
+    }
+
+    /// Forgets a wallet registered by a request that failed, and its directory, unless it has
+    /// been created since or replaced in the registry.
+    pub fn discard(
+        &self,
+        wallet_id: &str,
+        data: &web::Data<Mutex<ShiroWallet>>,
+    ) -> std::io::Result<()> {
+        let shiro_wallet = data.lock().unwrap();
+        if shiro_wallet.wallet.is_some() {
+            return Ok(());
+        }
+        {
+            let mut wallets = self.wallets.lock().unwrap();
+            match wallets.get(wallet_id) {
+                Some(registered) if Arc::ptr_eq(registered, data) => {}
+                _ => return Ok(()),
+            }
+            wallets.remove(wallet_id);
+        }
+        let data_dir = shiro_wallet.get_data_dir();
+        if Path::new(&data_dir).is_dir() {
+            std::fs::remove_dir_all(data_dir)?;
+        }
+        Ok(())
+    }
+
+    pub fn remove(&self, wallet_id: &str) -> Option<web::Data<Mutex<ShiroWallet>>> {
+        self.wallets.lock().unwrap().remove(wallet_id)
+    }
+
+    pub fn list(&self) -> Vec<(String, web::Data<Mutex<ShiroWallet>>)> {
+        let mut wallets = self
+            .wallets
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(wallet_id, shiro_wallet)| (wallet_id.clone(), shiro_wallet.clone()))
+            .collect::<Vec<_>>();
+        wallets.sort_by(|a, b| a.0.cmp(&b.0));
+        wallets
+    }
+}
+
+fn is_valid_wallet_id(wallet_id: &str) -> bool {
+    !wallet_id.is_empty()
+        && wallet_id.len() <= 64
+        && wallet_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Splits `/wallets/{wallet_id}/rest` into `(wallet_id, "/rest")`.
+fn split_path(path: &str) -> Option<(&str, &str)> {
+    let path = path.strip_prefix("/wallets/")?;
+    let (wallet_id, rest) = match path.find('/') {
+        Some(pos) => path.split_at(pos),
+        None => (path, ""),
+    };
+    if is_valid_wallet_id(wallet_id) {
+        Some((wallet_id, rest))
+    } else {
+        None
+    }
+}
+
+fn touch(data: &Mutex<ShiroWallet>) {
+    // A busy wallet is being used anyway.
+    if let Ok(mut shiro_wallet) = data.try_lock() {
+        shiro_wallet.last_used = Instant::now();
+    }
+}
+
+/// A wallet registered by the request being served, see [`WalletRegistry::discard`].
+struct NewWallet {
+    registry: web::Data<WalletRegistry>,
+    wallet_id: String,
+    data: web::Data<Mutex<ShiroWallet>>,
+}
+
+type Selected = (ServiceRequest, Option<NewWallet>);
+
+fn select(mut req: ServiceRequest) -> Result<Selected, ServiceResponse> {
+    // Percent-decoded like the router and the authentication see it
+    let path = req.match_info().unprocessed().to_string();
+    let (wallet_id, rest) = match split_path(&path) {
+        Some((wallet_id, rest)) => (wallet_id.to_string(), rest.to_string()),
+        None => {
+            if path == "/wallet" || path.starts_with("/wallet/") {
+                if let Some(data) = req.app_data::<web::Data<Mutex<ShiroWallet>>>() {
+                    touch(data);
+                }
+            }
+            return Ok((req, None));
+        }
+    };
+    // `DELETE /wallets/{wallet_id}` is served by the registry itself.
+    if rest.is_empty() && req.method() == Method::DELETE {
+        return Ok((req, None));
+    }
+    let registry = match req.app_data::<web::Data<WalletRegistry>>() {
+        Some(registry) => registry.clone(),
+        None => return Ok((req, None)),
+    };
+    let mut new_wallet = None;
+    // Creating a wallet or restoring it from a backup also registers it, a failed request
+    // doesn't leave the wallet behind.
+    let shiro_wallet = if (rest.is_empty() && req.method() == Method::PUT)
+        || (rest == "/restore" && req.method() == Method::POST)
+    {
+        match registry.get(&wallet_id) {
+            Some(shiro_wallet) => shiro_wallet,
+            None => match registry.get_or_insert(&wallet_id) {
+                Ok(shiro_wallet) => {
+                    new_wallet = Some(NewWallet {
+                        registry: registry.clone(),
+                        wallet_id: wallet_id.clone(),
+                        data: shiro_wallet.clone(),
+                    });
+                    shiro_wallet
+                }
+                Err(e) => {
+                    return Err(
+                        req.into_response(ShiroError::Internal(e.to_string()).error_response())
+                    )
+                }
+            },
+        }
+    } else {
+        match registry.get(&wallet_id) {
+            Some(shiro_wallet) => shiro_wallet,
+            None => return Err(req.into_response(ShiroError::WalletNotFound.error_response())),
+        }
+    };
+
+    touch(&shiro_wallet);
+
+    let mut parts = req.head().uri.clone().into_parts();
+    let path = match parts.path_and_query.as_ref().and_then(|pq| pq.query()) {
+        Some(query) => format!("/wallet{}?{}", rest, query),
+        None => format!("/wallet{}", rest),
+    };
+    parts.path_and_query = Some(PathAndQuery::try_from(path).unwrap());
+    let uri = Uri::from_parts(parts).unwrap();
+    req.match_info_mut().get_mut().update(&uri);
+    req.head_mut().uri = uri;
+
+    let mut container = Extensions::new();
+    container.insert(shiro_wallet);
+    req.add_data_container(Rc::new(container));
+    Ok((req, new_wallet))
+}
+
+/// Serves `/wallets/{wallet_id}/...` with the `/wallet/...` handlers, bound to the
+/// selected wallet of the [`WalletRegistry`], and records when a wallet is used.
+pub struct WalletSelector;
+
+impl<S, B> Transform<S, ServiceRequest> for WalletSelector
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = WalletSelectorMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(WalletSelectorMiddleware { service }))
+    }
+}
+
+pub struct WalletSelectorMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for WalletSelectorMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        match select(req) {
+            Ok((req, new_wallet)) => {
+                let fut = self.service.call(req);
+                Box::pin(async move {
+                    let result = fut.await;
+                    if let Some(new_wallet) = new_wallet {
+                        let failed = !matches!(&result, Ok(resp) if resp.status().is_success());
+                        if failed {
+                            let NewWallet {
+                                registry,
+                                wallet_id,
+                                data,
+                            } = new_wallet;
+                            let discarded =
+                                web::block(move || registry.discard(&wallet_id, &data)).await;
+                            if let Ok(Err(e)) = discarded {
+                                println!("failed to discard a wallet: {}", e);
+                            }
+                        }
+                    }
+                    result.map(ServiceResponse::map_into_left_body)
+                })
+            }
+            Err(resp) => Box::pin(async move { Ok(resp.map_into_right_body()) }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WalletEntry {
+    wallet_id: String,
+    created: bool,
+    online: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WalletsResult {
+    wallets: Vec<WalletEntry>,
+}
+
+#[get("/wallets")]
+pub async fn get(registry: web::Data<WalletRegistry>) -> impl Responder {
+    let wallets = registry
+        .list()
+        .into_iter()
+        .map(|(wallet_id, data)| {
+            let shiro_wallet = data.lock().unwrap();
+            WalletEntry {
+                wallet_id,
+                created: shiro_wallet.wallet.is_some(),
+                online: shiro_wallet.online.is_some(),
+            }
+        })
+        .collect::<Vec<WalletEntry>>();
+    HttpResponse::Ok().json(WalletsResult { wallets })
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WalletRemoveResult {}
+
+#[delete("/wallets/{wallet_id}")]
+pub async fn delete(
+    wallet_id: web::Path<String>,
+    registry: web::Data<WalletRegistry>,
+) -> impl Responder {
+    match registry.remove(&wallet_id) {
+        Some(data) => {
+            let data_dir = registry.get_wallet_data_dir(&wallet_id);
+            match blocking(move || {
+                // Wait for running operations on the wallet before deleting its files.
+                drop(data.lock().unwrap());
+                std::fs::remove_dir_all(data_dir).map_err(|e| ShiroError::Internal(e.to_string()))
+            })
+            .await
+            {
+                Ok(()) => HttpResponse::Ok().json(WalletRemoveResult {}),
+                Err(e) => e.error_response(),
+            }
+        }
+        None => ShiroError::WalletNotFound.error_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::wallet::{address::AddressResult, restore::tests::restore_request, WalletParams};
+    use actix_web::{http, test, App};
+    use rgb_lib::generate_keys;
+
+    #[test]
+    fn test_split_path() {
+        assert_eq!(split_path("/wallets/alice"), Some(("alice", "")));
+        assert_eq!(
+            split_path("/wallets/alice/address"),
+            Some(("alice", "/address"))
+        );
+        assert_eq!(split_path("/wallets/"), None);
+        assert_eq!(split_path("/wallets/../address"), None);
+        assert_eq!(split_path("/wallet/address"), None);
+    }
+
+    #[actix_web::test]
+    async fn test_wallets() {
+        let data_dir = shiro_backend::opts::get_wallet_data().data_dir;
+        let registry = web::Data::new(WalletRegistry::new(data_dir, false));
+        let app = test::init_service(
+            App::new()
+                .app_data(registry)
+                .wrap(WalletSelector)
+                .service(get)
+                .service(delete)
+                .service(crate::wallet::put)
+                .service(crate::wallet::close)
+                .service(crate::wallet::address::get),
+        )
+        .await;
+
+        {
+            let req = test::TestRequest::get()
+                .uri("/wallets/alice/address")
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+        }
+        for wallet_id in ["alice", "bob"] {
+            let keys = generate_keys(rgb_lib::BitcoinNetwork::Regtest);
+            let params = WalletParams {
+                mnemonic: Some(keys.mnemonic),
+                pubkey: keys.xpub,
+            };
+            let req = test::TestRequest::put()
+                .uri(&format!("/wallets/{}", wallet_id))
+                .set_json(params)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        let alice: AddressResult = {
+            let req = test::TestRequest::get()
+                .uri("/wallets/alice/address")
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+        let bob: AddressResult = {
+            let req = test::TestRequest::get()
+                .uri("/wallets/bob/address")
+                .to_request();
+            test::call_and_read_body_json(&app, req).await
+        };
+        assert_ne!(alice.new_address, bob.new_address);
+        {
+            let req = test::TestRequest::post()
+                .uri("/wallets/bob/close")
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let req = test::TestRequest::get().uri("/wallets").to_request();
+            let result: WalletsResult = test::call_and_read_body_json(&app, req).await;
+            assert_eq!(result.wallets.len(), 2);
+            assert!(result.wallets[0].created);
+            assert!(!result.wallets[1].created);
+        }
+        for wallet_id in ["alice", "bob"] {
+            let req = test::TestRequest::delete()
+                .uri(&format!("/wallets/{}", wallet_id))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            println!("{:?}", resp);
+            assert!(resp.status().is_success());
+        }
+        {
+            let req = test::TestRequest::get().uri("/wallets").to_request();
+            let result: WalletsResult = test::call_and_read_body_json(&app, req).await;
+            assert!(result.wallets.is_empty());
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_failed_creation() {
+        let data_dir = shiro_backend::opts::get_wallet_data().data_dir;
+        let registry = web::Data::new(WalletRegistry::new(data_dir, false));
+        let app = test::init_service(
+            App::new()
+                .app_data(registry.clone())
+                .wrap(WalletSelector)
+                .service(get)
+                .service(crate::wallet::put)
+                .service(crate::wallet::restore::post),
+        )
+        .await;
+
+        let params = WalletParams {
+            mnemonic: None,
+            pubkey: "not a pubkey".to_string(),
+        };
+        let req = test::TestRequest::put()
+            .uri("/wallets/dave")
+            .set_json(params)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(!resp.status().is_success());
+        assert!(!Path::new(&registry.get_wallet_data_dir("dave")).exists());
+
+        let req = restore_request("correct horse", None, b"not an archive")
+            .uri("/wallets/carol/restore")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        println!("{:?}", resp);
+        assert!(!resp.status().is_success());
+        {
+            let req = test::TestRequest::get().uri("/wallets").to_request();
+            let result: WalletsResult = test::call_and_read_body_json(&app, req).await;
+            assert!(result.wallets.is_empty());
+        }
+        assert!(!Path::new(&registry.get_wallet_data_dir("carol")).exists());
+    }
+}